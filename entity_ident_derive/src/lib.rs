@@ -0,0 +1,170 @@
+//! `#[derive(EntityId)]` for `entity_ident`.
+//!
+//! This crate is re-exported by `entity_ident` behind its `derive` feature; depend on that
+//! instead of using it directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, ExprArray, Fields, Lit, LitStr, Type, Visibility};
+
+/// Implements an id type for a struct wrapping an `Identifier`.
+///
+/// ```ignore
+/// /// A payment, either a card charge or a bank payment.
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EntityId)]
+/// #[entity_id(prefix = "ch", alt = ["py"], legacy = ["chg"], vis = "pub(crate)")]
+/// pub struct ChargeId(Identifier);
+/// ```
+///
+/// - `prefix` is the prefix used by `generate()`.
+/// - `alt` lists other prefixes that are accepted when parsing.
+/// - `legacy` lists deprecated prefixes that are still parsed but never generated.
+/// - `vis` is the visibility of the generated inherent methods, `pub` by default.
+///
+/// This generates everything `def_id!` does for a struct except the standard derives: `Clone`,
+/// `Copy`, `Debug`, `PartialEq`, `Eq` and `Hash` must be derived alongside, as above. `PartialOrd`
+/// and `Ord` are generated to order ids by their string, so those should not be derived.
+#[proc_macro_derive(EntityId, attributes(entity_id))]
+pub fn derive_entity_id(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+struct Args {
+    prefix: Option<LitStr>,
+    alt: Vec<LitStr>,
+    legacy: Vec<LitStr>,
+    vis: Option<Visibility>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    check_shape(&input)?;
+
+    let args = parse_args(&input)?;
+    let prefix = args.prefix.ok_or_else(|| {
+        syn::Error::new(
+            input.ident.span(),
+            "missing id prefix, add `#[entity_id(prefix = \"...\")]`",
+        )
+    })?;
+
+    let mut seen: Vec<String> = Vec::new();
+    for lit in std::iter::once(&prefix).chain(&args.alt).chain(&args.legacy) {
+        let value = lit.value();
+        check_prefix(lit, &value)?;
+        if seen.contains(&value) {
+            return Err(syn::Error::new(lit.span(), format!("duplicate prefix `{value}`")));
+        }
+        seen.push(value);
+    }
+
+    let ident = &input.ident;
+    let vis = args.vis.unwrap_or_else(|| syn::parse_quote!(pub));
+    let alt = &args.alt;
+    let legacy = &args.legacy;
+
+    Ok(quote! {
+        ::entity_ident::def_id_struct_impls!(#vis #ident, #prefix #(| #alt)*, legacy [#(#legacy),*]);
+    })
+}
+
+/// Ids must be a non-generic tuple struct with a single `Identifier` field.
+fn check_shape(input: &DeriveInput) -> syn::Result<()> {
+    const SHAPE: &str = "`EntityId` can only be derived for a tuple struct wrapping an `Identifier`, e.g. `struct UserId(Identifier);`";
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(input.ident.span(), SHAPE));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "`EntityId` cannot be derived for generic types",
+        ));
+    }
+    let Fields::Unnamed(fields) = &data.fields else {
+        return Err(syn::Error::new(data.fields.span(), SHAPE));
+    };
+    if fields.unnamed.len() != 1 {
+        return Err(syn::Error::new(fields.span(), SHAPE));
+    }
+    let field = &fields.unnamed[0];
+    let is_identifier = match &field.ty {
+        Type::Path(ty) => ty.qself.is_none() && ty.path.segments.last().is_some_and(|s| s.ident == "Identifier"),
+        _ => false,
+    };
+    if !is_identifier {
+        return Err(syn::Error::new(field.ty.span(), SHAPE));
+    }
+    Ok(())
+}
+
+fn parse_args(input: &DeriveInput) -> syn::Result<Args> {
+    let mut args = Args { prefix: None, alt: Vec::new(), legacy: Vec::new(), vis: None };
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("entity_id")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                if args.prefix.is_some() {
+                    return Err(meta.error("duplicate `prefix`, use `alt` for additional prefixes"));
+                }
+                args.prefix = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("alt") {
+                args.alt.extend(lit_strs(meta.value()?.parse()?)?);
+            } else if meta.path.is_ident("legacy") {
+                args.legacy.extend(lit_strs(meta.value()?.parse()?)?);
+            } else if meta.path.is_ident("vis") {
+                let lit: LitStr = meta.value()?.parse()?;
+                let vis = lit
+                    .parse::<Visibility>()
+                    .map_err(|_| syn::Error::new(lit.span(), "expected a visibility, e.g. `pub(crate)`"))?;
+                args.vis = Some(vis);
+            } else {
+                return Err(meta.error("unknown `entity_id` option, expected `prefix`, `alt`, `legacy` or `vis`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(args)
+}
+
+/// A single string prefix or an array of them.
+fn lit_strs(expr: Expr) -> syn::Result<Vec<LitStr>> {
+    match expr {
+        Expr::Array(ExprArray { elems, .. }) => elems.iter().map(lit_str).collect(),
+        expr => Ok(vec![lit_str(&expr)?]),
+    }
+}
+
+fn lit_str(expr: &Expr) -> syn::Result<LitStr> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => Ok(s.clone()),
+            other => Err(syn::Error::new(other.span(), "expected a string prefix")),
+        },
+        other => Err(syn::Error::new(other.span(), "expected a string prefix")),
+    }
+}
+
+/// Prefixes must be 1 to 8 ASCII alphanumerics, as checked by `Identifier::from_str`.
+fn check_prefix(lit: &LitStr, value: &str) -> syn::Result<()> {
+    let span: Span = lit.span();
+    if value.is_empty() {
+        return Err(syn::Error::new(span, "id prefix cannot be empty"));
+    }
+    if value.len() > 8 {
+        return Err(syn::Error::new(
+            span,
+            format!("id prefix `{value}` is {} characters long, at most 8 are allowed", value.len()),
+        ));
+    }
+    if let Some(c) = value.chars().find(|c| !c.is_ascii_alphanumeric()) {
+        return Err(syn::Error::new(
+            span,
+            format!("id prefix `{value}` contains `{c}`, only ASCII letters and digits are allowed"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,4 @@
+//! `libentity_ident_ffi.a` and `libentity_ident_ffi.so`, exporting the C ABI of
+//! `entity_ident::ffi` declared in `include/entity_ident.h`.
+
+pub use entity_ident::ffi::*;
@@ -0,0 +1,4 @@
+//! The `entity_ident` Python extension module, built with `maturin build` from the repository root
+//! (see `pyproject.toml`).
+
+pub use entity_ident::python::entity_ident;
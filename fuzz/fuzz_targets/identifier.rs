@@ -0,0 +1,26 @@
+#![no_main]
+
+use entity_ident::Identifier;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(id) = Identifier::from_bytes(data) else {
+        if let Ok(s) = std::str::from_utf8(data) {
+            assert!(Identifier::from_str(s).is_err());
+        }
+        return;
+    };
+
+    // Parsing and formatting round-trip
+    assert_eq!(id.as_bytes(), data);
+    assert!(id.len() <= 31);
+    assert_eq!(Identifier::from_str(id.as_str()).unwrap(), id);
+    assert_eq!(format!("{}_{}", id.prefix(), id.payload()), id.as_str());
+
+    // Anything `from_bytes` accepts satisfies the safety contract of `from_bytes_unchecked`
+    assert_eq!(unsafe { Identifier::from_bytes_unchecked(data) }, id);
+
+    if let Some(payload) = id.payload_bytes() {
+        assert_eq!(Identifier::from_payload(id.prefix(), payload).unwrap(), id);
+    }
+});
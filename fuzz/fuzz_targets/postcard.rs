@@ -0,0 +1,32 @@
+#![no_main]
+
+use entity_ident::def_id;
+use libfuzzer_sys::fuzz_target;
+
+def_id!(UserId, "user");
+def_id!(ChargeId, "ch" | "py");
+def_id!(
+    enum UserOrCharge {
+        Charge(ChargeId),
+        User(UserId),
+    }
+);
+
+fuzz_target!(|data: &[u8]| {
+    // postcard isn't human-readable, so ids go through `from_bytes` rather than `FromStr`
+    let raw = postcard::take_from_bytes::<&[u8]>(data).ok().map(|(raw, _)| raw);
+
+    let id = postcard::take_from_bytes::<ChargeId>(data).ok().map(|(id, _)| id);
+    assert_eq!(id, raw.and_then(|raw| ChargeId::from_bytes(raw).ok()));
+    if let Some(id) = id {
+        let bytes = postcard::to_allocvec(&id).unwrap();
+        assert_eq!(postcard::from_bytes::<ChargeId>(&bytes).unwrap(), id);
+    }
+
+    let either = postcard::take_from_bytes::<UserOrCharge>(data).ok().map(|(either, _)| either);
+    assert_eq!(either, raw.and_then(|raw| UserOrCharge::from_bytes(raw).ok()));
+    if let Some(either) = either {
+        let bytes = postcard::to_allocvec(&either).unwrap();
+        assert_eq!(postcard::from_bytes::<UserOrCharge>(&bytes).unwrap(), either);
+    }
+});
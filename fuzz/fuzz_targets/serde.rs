@@ -0,0 +1,25 @@
+#![no_main]
+
+use entity_ident::def_id;
+use libfuzzer_sys::fuzz_target;
+
+def_id!(UserId, "user");
+def_id!(ChargeId, "ch" | "py");
+def_id!(
+    enum UserOrCharge {
+        Charge(ChargeId),
+        User(UserId),
+    }
+);
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(id) = serde_json::from_slice::<ChargeId>(data) {
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(serde_json::from_str::<ChargeId>(&json).unwrap(), id);
+    }
+
+    if let Ok(either) = serde_json::from_slice::<UserOrCharge>(data) {
+        let json = serde_json::to_string(&either).unwrap();
+        assert_eq!(serde_json::from_str::<UserOrCharge>(&json).unwrap(), either);
+    }
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+use entity_ident::{def_id, Identifier};
+use libfuzzer_sys::fuzz_target;
+
+def_id!(UserId, "user");
+def_id!(ChargeId, "ch" | "py");
+def_id!(
+    enum UserOrCharge {
+        Charge(ChargeId),
+        User(UserId),
+    }
+);
+
+fuzz_target!(|data: &[u8]| {
+    let from_bytes = ChargeId::from_bytes(data);
+    if let Ok(id) = &from_bytes {
+        assert_eq!(id.as_bytes(), data);
+        assert!(ChargeId::is_valid_prefix(id.prefix()));
+        assert!(Identifier::from_bytes(data).is_ok());
+    }
+
+    let either = UserOrCharge::from_bytes(data);
+    if let Ok(either) = &either {
+        assert_eq!(either.as_bytes(), data);
+    }
+
+    if let Ok(s) = std::str::from_utf8(data) {
+        // The typed parsing paths agree with each other
+        assert_eq!(s.parse::<ChargeId>().ok(), from_bytes.ok());
+        assert_eq!(s.parse::<UserOrCharge>().ok(), either.ok());
+    }
+});
@@ -0,0 +1,94 @@
+#ifndef ENTITY_IDENT_H
+#define ENTITY_IDENT_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * An id, in the same 32-byte layout as `entity_ident::Identifier`.
+ *
+ * Only the `ei_*` functions should write to it.
+ */
+typedef struct ei_identifier {
+  uint8_t bytes[32];
+} ei_identifier;
+
+/**
+ * The call succeeded.
+ */
+#define EI_OK 0
+
+/**
+ * An argument was null or not a valid id or prefix.
+ */
+#define EI_INVALID 1
+
+/**
+ * The output buffer was too small, so nothing was written to it.
+ */
+#define EI_BUFFER_TOO_SMALL 2
+
+/**
+ * The size of a buffer large enough for any id, including its NUL terminator.
+ */
+#define EI_STRING_SIZE 32
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Parses the `len` bytes at `s` into `out`.
+ *
+ * Returns `EI_OK`, or `EI_INVALID` if `s` is not a valid id.
+ *
+ * # Safety
+ * `s` must point to `len` readable bytes, and `out` must be valid for writes.
+ */
+int ei_parse(const char *s, size_t len, ei_identifier *out);
+
+/**
+ * Generates a new id with the `len`-byte prefix at `prefix` into `out`.
+ *
+ * Returns `EI_OK`, or `EI_INVALID` if the prefix is not 1 to 8 ASCII alphanumerics.
+ *
+ * # Safety
+ * `prefix` must point to `len` readable bytes, and `out` must be valid for writes.
+ */
+int ei_generate(const char *prefix, size_t len, ei_identifier *out);
+
+/**
+ * Writes the prefix of `id` to `buf` as a NUL-terminated string, and its length to `len`.
+ *
+ * Returns `EI_OK`, `EI_INVALID` if `id` is null, or `EI_BUFFER_TOO_SMALL` if `buf_len` is not
+ * larger than the prefix, in which case nothing is written to `buf`.
+ *
+ * # Safety
+ * `id` must be null or filled in by `ei_parse` or `ei_generate`, `buf` must be null or valid for
+ * `buf_len` bytes of writes, and `len` must be null or valid for writes.
+ */
+int ei_prefix(const ei_identifier *id, char *buf, size_t buf_len, size_t *len);
+
+/**
+ * Writes `id` to `buf` as a NUL-terminated string, and its length to `len`.
+ *
+ * Returns `EI_OK`, `EI_INVALID` if `id` is null, or `EI_BUFFER_TOO_SMALL` if `buf_len` is not
+ * larger than the id, in which case nothing is written to `buf`; a buffer of `EI_STRING_SIZE`
+ * bytes always suffices.
+ *
+ * # Safety
+ * `id` must be null or filled in by `ei_parse` or `ei_generate`, `buf` must be null or valid for
+ * `buf_len` bytes of writes, and `len` must be null or valid for writes.
+ */
+int ei_to_string(const ei_identifier *id, char *buf, size_t buf_len, size_t *len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ENTITY_IDENT_H */
@@ -0,0 +1,97 @@
+//! `actix-web` support.
+//!
+//! [`Path`] and [`Query`] extract an id from the request path or query string, and
+//! [`ParseIdError`] is a `ResponseError`, so handlers can take ids directly and return parse
+//! errors with `?`. Either way an invalid id is answered with `400 Bad Request` and a JSON body
+//! naming the id type and what was expected.
+//!
+//! Ids are looked up by the snake_case name of their type, so `Path<ChargeId>` reads the
+//! `{charge_id}` segment of `/users/{user_id}/charges/{charge_id}`. A route's only path parameter
+//! can also be called `{id}`, as in `/charges/{id}`.
+
+use std::collections::HashMap;
+
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, ResponseError};
+
+use crate::{EntityId, ParseIdError};
+
+/// Extracts an id from the request path, e.g. `Path<UserId>` for `/users/{user_id}`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Path<T>(pub T);
+
+impl<T: EntityId> FromRequest for Path<T> {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let params = req.match_info();
+        let name = crate::param_name(T::TYPE_NAME);
+        let raw = match params.get(&name) {
+            Some(raw) => Some(raw),
+            None if params.segment_count() == 1 => params.get("id"),
+            None => None,
+        };
+        let result = match raw {
+            Some(raw) => raw.parse().map(Path).map_err(actix_web::Error::from),
+            None => Err(actix_web::error::ErrorInternalServerError(format!(
+                "route has no `{name}` path parameter for `{}`",
+                T::TYPE_NAME,
+            ))),
+        };
+        std::future::ready(result)
+    }
+}
+
+impl<T> std::ops::Deref for Path<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Extracts an id from the query string, e.g. `Query<UserId>` for `/charges?user_id=...`.
+///
+/// A missing parameter is answered like an invalid id.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Query<T>(pub T);
+
+impl<T: EntityId> FromRequest for Query<T> {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let result = actix_web::web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .map_err(actix_web::Error::from)
+            .and_then(|params| {
+                let result = match params.get(&crate::param_name(T::TYPE_NAME)) {
+                    Some(raw) => raw.parse(),
+                    None => Err(ParseIdError::new(T::TYPE_NAME, "an id").with_prefixes(T::PREFIXES)),
+                };
+                result.map(Query).map_err(actix_web::Error::from)
+            });
+        std::future::ready(result)
+    }
+}
+
+impl<T> std::ops::Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl ResponseError for ParseIdError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .content_type("application/json")
+            .body(self.to_json().to_string())
+    }
+}
@@ -0,0 +1,98 @@
+//! `axum` support.
+//!
+//! [`Path`] and [`Query`] extract an id from the request path or query string, and
+//! [`ParseIdError`] is a response in its own right, so handlers can take ids directly and return
+//! parse errors with `?`. Either way an invalid id is answered with `400 Bad Request` and a JSON
+//! body naming the id type and what was expected.
+//!
+//! Ids are looked up by the snake_case name of their type, so `Path<ChargeId>` reads the
+//! `{charge_id}` parameter of `/users/{user_id}/charges/{charge_id}`. A route's only path
+//! parameter can also be called `{id}`, as in `/charges/{id}`.
+
+use std::collections::HashMap;
+
+use ::axum::extract::{FromRequestParts, RawPathParams};
+use ::axum::http::request::Parts;
+use ::axum::http::{header, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+
+use crate::{EntityId, ParseIdError};
+
+/// Extracts an id from the request path, e.g. `Path<UserId>` for `/users/{user_id}`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Path<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: EntityId + Send,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let name = crate::param_name(T::TYPE_NAME);
+        let mut only = params.iter();
+        let raw = match (params.iter().find(|&(key, _)| key == name), only.next(), only.next()) {
+            (Some((_, raw)), _, _) | (None, Some(("id", raw)), None) => raw,
+            _ => {
+                let message = format!("route has no `{name}` path parameter for `{}`", T::TYPE_NAME);
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, message).into_response());
+            }
+        };
+        raw.parse().map(Path).map_err(IntoResponse::into_response)
+    }
+}
+
+impl<T> std::ops::Deref for Path<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Extracts an id from the query string, e.g. `Query<UserId>` for `/charges?user_id=...`.
+///
+/// A missing parameter is answered like an invalid id.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Query<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: EntityId + Send,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let ::axum::extract::Query(params) = ::axum::extract::Query::<HashMap<String, String>>::try_from_uri(&parts.uri)
+            .map_err(IntoResponse::into_response)?;
+        let result = match params.get(&crate::param_name(T::TYPE_NAME)) {
+            Some(raw) => raw.parse(),
+            None => Err(ParseIdError::new(T::TYPE_NAME, "an id").with_prefixes(T::PREFIXES)),
+        };
+        result.map(Query).map_err(IntoResponse::into_response)
+    }
+}
+
+impl<T> std::ops::Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl IntoResponse for ParseIdError {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "application/json")],
+            self.to_json().to_string(),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,106 @@
+//! `clap` support.
+//!
+//! `def_id!` types implement `ValueParserFactory`, so `value_parser!(UserId)` (and the derive API)
+//! validates ids while arguments are parsed, with the same error message as `FromStr`. The accepted
+//! prefixes are offered to shell completion as `prefix_` hints, described by
+//! [`IdValueParser::help`], which can also be used as an argument's help:
+//!
+//! ```ignore
+//! Arg::new("charge").value_parser(value_parser!(ChargeId)).help(value_parser!(ChargeId).help())
+//! ```
+
+use std::ffi::OsStr;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{Arg, Command};
+
+use crate::ParseIdError;
+
+/// Parses a command line argument into the id type `T`.
+pub struct IdValueParser<T> {
+    typename: &'static str,
+    prefixes: Vec<&'static str>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> IdValueParser<T> {
+    /// A parser for ids of type `typename`, which start with one of `prefixes`.
+    pub fn new(typename: &'static str, prefixes: Vec<&'static str>) -> Self {
+        IdValueParser {
+            typename,
+            prefixes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Help text describing the accepted ids, e.g. "A ChargeId starting with `ch_` or `py_`".
+    pub fn help(&self) -> String {
+        let prefixes = self.prefixes.iter().map(|prefix| format!("`{prefix}_`")).collect::<Vec<_>>();
+        format!("A {} starting with {}", self.typename, prefixes.join(" or "))
+    }
+}
+
+impl<T> Clone for IdValueParser<T> {
+    fn clone(&self) -> Self {
+        IdValueParser::new(self.typename, self.prefixes.clone())
+    }
+}
+
+impl<T> TypedValueParser for IdValueParser<T>
+where
+    T: FromStr<Err = ParseIdError> + Clone + Send + Sync + 'static,
+{
+    type Value = T;
+
+    fn parse_ref(&self, cmd: &Command, arg: Option<&Arg>, value: &OsStr) -> Result<T, clap::Error> {
+        let arg = arg.map_or_else(|| "...".to_string(), |arg| arg.to_string());
+        let value = value
+            .to_str()
+            .ok_or_else(|| clap::Error::raw(ErrorKind::InvalidUtf8, format!("invalid UTF-8 in '{arg}'\n")).with_cmd(cmd))?;
+
+        value.parse().map_err(|err: ParseIdError| {
+            let message = format!("invalid value '{value}' for '{arg}': {err}\n");
+            clap::Error::raw(ErrorKind::ValueValidation, message).with_cmd(cmd)
+        })
+    }
+
+    /// The accepted prefixes as completion hints (e.g. `ch_`), which don't restrict the values
+    /// `parse_ref` accepts.
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let help = self.help();
+        Some(Box::new(
+            self.prefixes
+                .iter()
+                .map(move |prefix| PossibleValue::new(format!("{prefix}_")).help(help.clone()).hide(false)),
+        ))
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_clap_impls {
+    ($struct_name:ident) => {
+        impl $crate::__private::clap::builder::ValueParserFactory for $struct_name {
+            type Parser = $crate::cli::IdValueParser<Self>;
+
+            fn value_parser() -> Self::Parser {
+                $crate::cli::IdValueParser::new(stringify!($struct_name), Self::prefixes().to_vec())
+            }
+        }
+    };
+    (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+        impl $crate::__private::clap::builder::ValueParserFactory for $enum_name {
+            type Parser = $crate::cli::IdValueParser<Self>;
+
+            fn value_parser() -> Self::Parser {
+                $crate::cli::IdValueParser::new(
+                    stringify!($enum_name),
+                    [$( <$($variant_type)*>::prefixes() ),*].concat(),
+                )
+            }
+        }
+    };
+}
@@ -0,0 +1,62 @@
+use core::fmt::{Debug, Display};
+use core::hash::Hash;
+use core::str::FromStr;
+
+use alloc::string::String;
+
+use crate::{Identifier, ParseIdError};
+
+/// An id type declared with `def_id!` (either form) or `#[derive(EntityId)]`.
+///
+/// This lets generic code work with any id type:
+///
+/// ```ignore
+/// fn load<T: EntityId>(id: T) -> Option<Row> {
+///     db.get(T::TYPE_NAME, id.inner().as_str())
+/// }
+/// ```
+pub trait EntityId:
+    Copy + Eq + Ord + Hash + Debug + Display + FromStr<Err = ParseIdError> + Into<String> + 'static
+{
+    /// The name of the id type (e.g. `ChargeId`).
+    const TYPE_NAME: &'static str;
+
+    /// The valid prefixes of the id type, starting with the default one.
+    const PREFIXES: &'static [&'static str];
+
+    /// The prefixes that are parsed but never generated, and not listed in `PREFIXES`.
+    const LEGACY_PREFIXES: &'static [&'static str];
+
+    /// The prefix used by [`generate`](EntityId::generate).
+    const DEFAULT_PREFIX: &'static str;
+
+    /// The untyped id.
+    fn inner(&self) -> &Identifier;
+
+    /// Wraps an already validated id, checking that its prefix is one of `PREFIXES` or
+    /// `LEGACY_PREFIXES`.
+    fn from_identifier(id: Identifier) -> Result<Self, ParseIdError>;
+
+    /// The id with a legacy prefix replaced by the default one, keeping its payload.
+    fn canonicalize(self) -> Self;
+
+    /// Whether the id has one of the `LEGACY_PREFIXES`.
+    fn is_legacy(&self) -> bool {
+        Self::LEGACY_PREFIXES.contains(&self.inner().prefix())
+    }
+
+    /// Parses an id of this type.
+    fn parse(s: &str) -> Result<Self, ParseIdError> {
+        s.parse()
+    }
+
+    /// Generates a new id with the default prefix.
+    #[cfg(feature = "getrandom")]
+    fn generate() -> Self {
+        match Identifier::generate(Self::DEFAULT_PREFIX).map(Self::from_identifier) {
+            Ok(Ok(id)) => id,
+            // Prefixes are checked at compile time by `def_id_assert_prefix!`
+            _ => unreachable!(),
+        }
+    }
+}
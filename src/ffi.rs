@@ -0,0 +1,124 @@
+//! A C ABI for parsing and generating ids.
+//!
+//! Ids cross the boundary as `ei_identifier`, the 32-byte in-memory form of [`Identifier`], so C
+//! callers can keep them on the stack and copy them freely. Strings are passed as a pointer and
+//! a length and need not be NUL-terminated. The header is checked in at `include/entity_ident.h`
+//! and generated with `cbindgen --config cbindgen.toml --output include/entity_ident.h`; the
+//! `entity_ident_ffi` crate builds the static and dynamic libraries to link against.
+
+use core::ffi::c_char;
+use core::ffi::c_int;
+
+use crate::Identifier;
+
+/// The call succeeded.
+pub const EI_OK: c_int = 0;
+
+/// An argument was null or not a valid id or prefix.
+pub const EI_INVALID: c_int = 1;
+
+/// The output buffer was too small, so nothing was written to it.
+pub const EI_BUFFER_TOO_SMALL: c_int = 2;
+
+/// The size of a buffer large enough for any id, including its NUL terminator.
+pub const EI_STRING_SIZE: usize = 32;
+
+unsafe fn parse_bytes<'a>(s: *const c_char, len: usize) -> Option<&'a [u8]> {
+    if s.is_null() {
+        return None;
+    }
+    Some(core::slice::from_raw_parts(s.cast(), len))
+}
+
+/// Copies `s` into `buf` with a NUL terminator, storing the length of `s` in `len` if it is not
+/// null.
+///
+/// Nothing is written to a null or too small `buf`, but `len` still tells the size needed (minus
+/// the terminator).
+unsafe fn write_str(s: &str, buf: *mut c_char, buf_len: usize, len: *mut usize) -> c_int {
+    if !len.is_null() {
+        len.write(s.len());
+    }
+    if buf.is_null() || s.len() >= buf_len {
+        return EI_BUFFER_TOO_SMALL;
+    }
+    core::ptr::copy_nonoverlapping(s.as_ptr(), buf.cast(), s.len());
+    *buf.add(s.len()) = 0;
+    EI_OK
+}
+
+/// Parses the `len` bytes at `s` into `out`.
+///
+/// Returns `EI_OK`, or `EI_INVALID` if `s` is not a valid id.
+///
+/// # Safety
+/// `s` must point to `len` readable bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ei_parse(s: *const c_char, len: usize, out: *mut Identifier) -> c_int {
+    let (Some(bytes), false) = (parse_bytes(s, len), out.is_null()) else {
+        return EI_INVALID;
+    };
+    match Identifier::from_bytes(bytes) {
+        Ok(id) => {
+            out.write(id);
+            EI_OK
+        }
+        Err(_) => EI_INVALID,
+    }
+}
+
+/// Generates a new id with the `len`-byte prefix at `prefix` into `out`.
+///
+/// Returns `EI_OK`, or `EI_INVALID` if the prefix is not 1 to 8 ASCII alphanumerics.
+///
+/// # Safety
+/// `prefix` must point to `len` readable bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ei_generate(prefix: *const c_char, len: usize, out: *mut Identifier) -> c_int {
+    let (Some(bytes), false) = (parse_bytes(prefix, len), out.is_null()) else {
+        return EI_INVALID;
+    };
+    let Ok(prefix) = core::str::from_utf8(bytes) else {
+        return EI_INVALID;
+    };
+    match Identifier::generate(prefix) {
+        Ok(id) => {
+            out.write(id);
+            EI_OK
+        }
+        Err(_) => EI_INVALID,
+    }
+}
+
+/// Writes the prefix of `id` to `buf` as a NUL-terminated string, and its length to `len`.
+///
+/// Returns `EI_OK`, `EI_INVALID` if `id` is null, or `EI_BUFFER_TOO_SMALL` if `buf_len` is not
+/// larger than the prefix, in which case nothing is written to `buf`.
+///
+/// # Safety
+/// `id` must be null or filled in by `ei_parse` or `ei_generate`, `buf` must be null or valid for
+/// `buf_len` bytes of writes, and `len` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ei_prefix(id: *const Identifier, buf: *mut c_char, buf_len: usize, len: *mut usize) -> c_int {
+    match id.as_ref() {
+        Some(id) => write_str(id.prefix(), buf, buf_len, len),
+        None => EI_INVALID,
+    }
+}
+
+/// Writes `id` to `buf` as a NUL-terminated string, and its length to `len`.
+///
+/// Returns `EI_OK`, `EI_INVALID` if `id` is null, or `EI_BUFFER_TOO_SMALL` if `buf_len` is not
+/// larger than the id, in which case nothing is written to `buf`; a buffer of `EI_STRING_SIZE`
+/// bytes always suffices.
+///
+/// # Safety
+/// `id` must be null or filled in by `ei_parse` or `ei_generate`, `buf` must be null or valid for
+/// `buf_len` bytes of writes, and `len` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ei_to_string(id: *const Identifier, buf: *mut c_char, buf_len: usize, len: *mut usize) -> c_int {
+    match id.as_ref() {
+        Some(id) => write_str(id.as_str(), buf, buf_len, len),
+        None => EI_INVALID,
+    }
+}
@@ -0,0 +1,72 @@
+//! GraphQL support via `async-graphql`.
+//!
+//! Each `def_id!` type becomes a custom scalar named after the type (e.g. `scalar UserId`) whose
+//! description lists the accepted prefixes. Inputs are parsed like any other id, so a value with
+//! another type's prefix is rejected before it reaches a resolver.
+
+/// The description of the scalar for an id type accepting `prefixes`, and made of `variants` if it
+/// is a `def_id!(enum ...)`.
+#[doc(hidden)]
+pub fn description(type_name: &str, variants: &[&str], prefixes: &[&str]) -> String {
+    let mut description = format!("A `{type_name}`, ");
+    if !variants.is_empty() {
+        description.push_str(&format!("which is one of {}, ", list(variants, "")));
+    }
+    description.push_str(&format!("starting with {}.", list(prefixes, "_")));
+    description
+}
+
+/// Formats `items` as "`a`, `b` or `c`".
+fn list(items: &[&str], suffix: &str) -> String {
+    let mut list = String::new();
+    for (i, item) in items.iter().enumerate() {
+        let separator = match i {
+            0 => "",
+            _ if i == items.len() - 1 => " or ",
+            _ => ", ",
+        };
+        list.push_str(&format!("{separator}`{item}{suffix}`"));
+    }
+    list
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_async_graphql_impls {
+    ($struct_name:ident) => {
+        $crate::def_id_async_graphql_impls!(@scalar $struct_name, &[]);
+    };
+    (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+        $crate::def_id_async_graphql_impls!(@scalar $enum_name, &[$( stringify!($($variant_type)*) ),*]);
+    };
+    (@scalar $name:ident, $variants:expr) => {
+        impl $crate::__private::async_graphql::Description for $name {
+            fn description() -> &'static str {
+                static DESCRIPTION: $crate::__private::OnceLock<$crate::__private::String> = $crate::__private::OnceLock::new();
+                DESCRIPTION.get_or_init(|| $crate::graphql::description(stringify!($name), $variants, <$name>::PREFIXES))
+            }
+        }
+
+        #[$crate::__private::async_graphql::Scalar(crate = $crate::__private::async_graphql, use_type_description)]
+        impl $crate::__private::async_graphql::ScalarType for $name {
+            fn parse(
+                value: $crate::__private::async_graphql::Value,
+            ) -> $crate::__private::async_graphql::InputValueResult<Self> {
+                match value {
+                    $crate::__private::async_graphql::Value::String(ref s) => {
+                        s.parse().map_err($crate::__private::async_graphql::InputValueError::custom)
+                    }
+                    value => Err($crate::__private::async_graphql::InputValueError::expected_type(value)),
+                }
+            }
+
+            fn is_valid(value: &$crate::__private::async_graphql::Value) -> bool {
+                matches!(value, $crate::__private::async_graphql::Value::String(s) if s.parse::<Self>().is_ok())
+            }
+
+            fn to_value(&self) -> $crate::__private::async_graphql::Value {
+                $crate::__private::async_graphql::Value::String(self.as_str().into())
+            }
+        }
+    };
+}
@@ -1,9 +1,32 @@
-use lazy_static::lazy_static;
-use regex::bytes::Regex;
+use alloc::string::String;
+use alloc::vec::Vec;
 
-lazy_static! {
-    static ref IDENTIFIER_REGEX: Regex = Regex::new(r"^([a-zA-Z0-9]{1,8})_([a-zA-Z0-9]{1,22})$").unwrap();
-    static ref PREFIX_REGEX: Regex = Regex::new(r"^([a-zA-Z0-9]{1,8})$").unwrap();
+/// Checks that `bytes` is 1 to `max_len` ASCII alphanumerics, as in `[a-zA-Z0-9]{1,max_len}`.
+const fn is_alphanumeric(bytes: &[u8], max_len: usize) -> bool {
+    if bytes.is_empty() || bytes.len() > max_len {
+        return false;
+    }
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_alphanumeric() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Checks that `bytes` is a valid prefix, matching `^[a-zA-Z0-9]{1,8}$`.
+pub const fn is_valid_prefix(bytes: &[u8]) -> bool {
+    is_alphanumeric(bytes, 8)
+}
+
+/// Checks that `bytes` is a valid id, matching `^([a-zA-Z0-9]{1,8})_([a-zA-Z0-9]{1,22})$`.
+fn is_valid_identifier(bytes: &[u8]) -> bool {
+    match bytes.iter().position(|&b| b == b'_') {
+        Some(sep) => is_valid_prefix(&bytes[..sep]) && is_alphanumeric(&bytes[sep + 1..], 22),
+        None => false,
+    }
 }
 
 // Representation:
@@ -27,7 +50,7 @@ impl Identifier {
     #[inline]
     pub fn as_str(&self) -> &str {
         // SAFETY: Identifier cannot be constructed from invalid UTF-8
-        unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
+        unsafe { core::str::from_utf8_unchecked(self.as_bytes()) }
     }
 
     #[inline]
@@ -35,6 +58,7 @@ impl Identifier {
         &self.bytes[1..=self.len()]
     }
 
+    #[allow(clippy::should_implement_trait)]
     #[inline]
     pub fn from_str(s: &str) -> Result<Self, InvalidIdentifierError> {
         Self::from_bytes(s.as_bytes())
@@ -46,8 +70,7 @@ impl Identifier {
             return Err(InvalidIdentifierError);
         }
 
-        // Use Regex to check the format
-        if !IDENTIFIER_REGEX.is_match(bytes) {
+        if !is_valid_identifier(bytes) {
             return Err(InvalidIdentifierError);
         }
 
@@ -62,7 +85,7 @@ impl Identifier {
     pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> Self {
         let len = bytes.len();
         debug_assert!(len <= 31);
-        debug_assert!(IDENTIFIER_REGEX.is_match(bytes));
+        debug_assert!(is_valid_identifier(bytes));
 
         let mut id_bytes = [0; 32];
         id_bytes[0] = len as u8;
@@ -76,53 +99,101 @@ impl Identifier {
         Self::from_bytes_unchecked(s.as_bytes())
     }
 
+    /// The position of the `_` separator.
+    #[inline]
+    fn separator(&self) -> usize {
+        self.as_bytes().iter().position(|&b| b == b'_').unwrap()
+    }
+
     pub fn prefix(&self) -> &str {
-        let bytes = self.as_bytes();
+        &self.as_str()[..self.separator()]
+    }
 
-        let caps = IDENTIFIER_REGEX.captures(bytes).unwrap();
-        let prefix_bytes = caps.get(1).unwrap().as_bytes();
-        unsafe { std::str::from_utf8_unchecked(prefix_bytes) }
+    /// The random part of the id, after the `_` separator.
+    pub fn payload(&self) -> &str {
+        &self.as_str()[self.separator() + 1..]
     }
 
-    pub fn generate(prefix: &str) -> Result<Self, InvalidIdentifierError> {
-        if !PREFIX_REGEX.is_match(prefix.as_bytes()) {
-            return Err(InvalidIdentifierError);
+    /// Decodes the payload back into the 128 random bits it encodes.
+    ///
+    /// Returns `None` if the payload is not the canonical base62 encoding of a 128-bit number,
+    /// which can only happen for ids that were not produced by `generate`.
+    pub fn payload_bytes(&self) -> Option<[u8; 16]> {
+        let payload = self.payload().as_bytes();
+        if payload.len() > 1 && payload[0] == b'0' {
+            return None;
         }
+        let num = base62::decode(payload).ok()?;
+        Some(num.to_be_bytes())
+    }
 
-        let mut rand_bytes: [u8; 16] = [0; 16];
-        getrandom::getrandom(&mut rand_bytes).unwrap();
-    
-        let num: u128 = u128::from_be_bytes(rand_bytes);
+    /// Builds an id from a prefix and the 128 random bits of its payload.
+    ///
+    /// This is the inverse of `payload_bytes`.
+    pub fn from_payload(prefix: &str, payload: [u8; 16]) -> Result<Self, InvalidIdentifierError> {
+        if !is_valid_prefix(prefix.as_bytes()) {
+            return Err(InvalidIdentifierError);
+        }
 
-        // TODO: Encode to bytes and avoid this allocation
-        let encoded_num = base62::encode(num);
+        let num: u128 = u128::from_be_bytes(payload);
 
         let mut bytes = [0; 32];
+        bytes[1..=prefix.len()].copy_from_slice(prefix.as_bytes());
+        bytes[prefix.len() + 1] = b'_';
+        let encoded_len = base62::encode_bytes(num, &mut bytes[prefix.len() + 2..]).unwrap();
 
         // Zero-index records the size
-        let len = (prefix.len() + 1 + encoded_num.len()) as u8;
+        let len = (prefix.len() + 1 + encoded_len) as u8;
         debug_assert!(len <= 31);
         bytes[0] = len;
 
+        debug_assert!(is_valid_identifier(&bytes[1..(bytes[0]+1) as usize]));
+
+        Ok(Self { bytes })
+    }
+
+    /// The same id with its prefix replaced by `prefix`.
+    pub fn with_prefix(&self, prefix: &str) -> Result<Self, InvalidIdentifierError> {
+        if !is_valid_prefix(prefix.as_bytes()) {
+            return Err(InvalidIdentifierError);
+        }
+        let payload = self.payload();
+
+        // A prefix of at most 8 bytes, the separator and a payload of at most 22 always fit
+        let len = prefix.len() + 1 + payload.len();
+        let mut bytes = [0; 32];
+        bytes[0] = len as u8;
         bytes[1..=prefix.len()].copy_from_slice(prefix.as_bytes());
         bytes[prefix.len() + 1] = b'_';
-        bytes[prefix.len() + 2..=len as usize].copy_from_slice(encoded_num.as_bytes());
+        bytes[prefix.len() + 2..=len].copy_from_slice(payload.as_bytes());
 
-        debug_assert!(IDENTIFIER_REGEX.is_match(&bytes[1..(bytes[0]+1) as usize]));
+        debug_assert!(is_valid_identifier(&bytes[1..=len]));
 
         Ok(Self { bytes })
     }
 
+    #[cfg(feature = "getrandom")]
+    pub fn generate(prefix: &str) -> Result<Self, InvalidIdentifierError> {
+        let mut rand_bytes: [u8; 16] = [0; 16];
+        getrandom::getrandom(&mut rand_bytes).unwrap();
+
+        Self::from_payload(prefix, rand_bytes)
+    }
+
+    /// Converts the id into the `def_id!` type `T`, failing if `T` does not accept its prefix.
+    pub fn try_into_typed<T: crate::EntityId>(self) -> Result<T, crate::ParseIdError> {
+        T::from_identifier(self)
+    }
 }
 
-impl std::fmt::Debug for Identifier {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Identifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
-impl std::fmt::Display for Identifier {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Identifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
@@ -149,7 +220,7 @@ impl PartialEq<String> for Identifier {
 
 impl PartialOrd for Identifier {
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         self.as_bytes().partial_cmp(other.as_bytes())
     }
 }
@@ -160,7 +231,7 @@ impl AsRef<str> for Identifier {
     }
 }
 
-impl std::ops::Deref for Identifier {
+impl core::ops::Deref for Identifier {
     type Target = str;
 
     fn deref(&self) -> &str {
@@ -194,7 +265,7 @@ impl TryFrom<&[u8]> for Identifier {
 
 impl From<Identifier> for String {
     fn from(id: Identifier) -> Self {
-        id.as_str().to_string()
+        String::from(id.as_str())
     }
 }
 
@@ -204,7 +275,7 @@ impl From<Identifier> for Vec<u8> {
     }
 }
 
-impl std::str::FromStr for Identifier {
+impl core::str::FromStr for Identifier {
     type Err = InvalidIdentifierError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -215,18 +286,25 @@ impl std::str::FromStr for Identifier {
 #[derive(Debug)]
 pub struct InvalidIdentifierError;
 
-impl std::fmt::Display for InvalidIdentifierError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for InvalidIdentifierError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "invalid identifier")
     }
 }
 
-impl std::error::Error for InvalidIdentifierError {
+impl core::error::Error for InvalidIdentifierError {
     fn description(&self) -> &str {
         "error parsing an id"
     }
 }
 
+#[cfg(feature = "tonic")]
+impl From<InvalidIdentifierError> for tonic::Status {
+    fn from(err: InvalidIdentifierError) -> Self {
+        tonic::Status::invalid_argument(err.to_string())
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -288,4 +366,21 @@ mod tests {
         assert!(id2 == id);
         assert!(id2 == id);
     }
+
+    #[test]
+    pub fn test_payload() {
+        let id = Identifier::generate("test").unwrap();
+        let payload = id.payload_bytes().unwrap();
+        assert_eq!(Identifier::from_payload("test", payload).unwrap(), id);
+
+        let id = Identifier::from_str("test_C3M2XCLwa3LjkkH4V15muQ").unwrap();
+        assert_eq!(id.payload(), "C3M2XCLwa3LjkkH4V15muQ");
+
+        let id = Identifier::from_payload("u", [0; 16]).unwrap();
+        assert_eq!(id, "u_0");
+
+        // Leading zeros and payloads too large for 128 bits have no byte form
+        assert!(Identifier::from_str("u_0C").unwrap().payload_bytes().is_none());
+        assert!(Identifier::from_str("u_zzzzzzzzzzzzzzzzzzzzzz").unwrap().payload_bytes().is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+//! JSON Schema support via `schemars`.
+//!
+//! `def_id!` types describe themselves as strings matching their own prefixes, so generated
+//! OpenAPI documents show e.g. `^(ch|py)_[a-zA-Z0-9]{1,22}$` instead of a bare string.
+
+use std::borrow::Cow;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::Identifier;
+
+impl JsonSchema for Identifier {
+    fn schema_name() -> Cow<'static, str> {
+        "Identifier".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "entity_ident::Identifier".into()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        id_schema(&crate::__private::id_pattern(&[]), None)
+    }
+}
+
+/// The schema of an id matching `pattern`, with an optional example.
+#[doc(hidden)]
+pub fn id_schema(pattern: &str, example: Option<&str>) -> Schema {
+    let mut schema = json_schema!({
+        "type": "string",
+        "pattern": pattern,
+        "maxLength": 31,
+    });
+    if let Some(example) = example {
+        schema.insert("examples".into(), vec![example].into());
+    }
+    schema
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_schemars_impls {
+    ($struct_name:ident) => {
+        impl $crate::__private::schemars::JsonSchema for $struct_name {
+            fn schema_name() -> $crate::__private::Cow<'static, str> {
+                stringify!($struct_name).into()
+            }
+
+            fn schema_id() -> $crate::__private::Cow<'static, str> {
+                concat!(module_path!(), "::", stringify!($struct_name)).into()
+            }
+
+            fn json_schema(_gen: &mut $crate::__private::schemars::SchemaGenerator) -> $crate::__private::schemars::Schema {
+                let example = $crate::__private::example_id(Self::default_prefix());
+                $crate::json_schema::id_schema(
+                    &$crate::__private::id_pattern(Self::PREFIXES),
+                    example.as_ref().map($crate::Identifier::as_str),
+                )
+            }
+        }
+    };
+    (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+        impl $crate::__private::schemars::JsonSchema for $enum_name {
+            fn schema_name() -> $crate::__private::Cow<'static, str> {
+                stringify!($enum_name).into()
+            }
+
+            fn schema_id() -> $crate::__private::Cow<'static, str> {
+                concat!(module_path!(), "::", stringify!($enum_name)).into()
+            }
+
+            fn json_schema(gen: &mut $crate::__private::schemars::SchemaGenerator) -> $crate::__private::schemars::Schema {
+                let variants = $crate::__private::vec![$( gen.subschema_for::<$($variant_type)*>().to_value(), )*];
+                $crate::__private::schemars::json_schema!({ "oneOf": variants })
+            }
+        }
+    };
+}
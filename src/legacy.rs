@@ -0,0 +1,42 @@
+//! Reporting ids with legacy prefixes.
+//!
+//! Prefixes declared with `def_id!(CustomerId, "cus", legacy: "cust")` keep parsing but are never
+//! generated. Install a hook to find out where old ids are still coming from:
+//!
+//! ```ignore
+//! entity_ident::legacy::set_hook(|type_name, id| {
+//!     tracing::warn!(%id, "legacy {type_name} prefix");
+//! });
+//! ```
+
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::Identifier;
+
+/// Called with the id type's name (e.g. `CustomerId`) and the id whenever an id with a legacy
+/// prefix is parsed or converted into that type.
+pub type Hook = fn(type_name: &'static str, id: &Identifier);
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Installs the process-wide legacy prefix hook, replacing any previous one.
+pub fn set_hook(hook: Hook) {
+    HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+/// Removes the legacy prefix hook, returning it if one was installed.
+pub fn take_hook() -> Option<Hook> {
+    let hook = HOOK.swap(core::ptr::null_mut(), Ordering::AcqRel);
+    // SAFETY: `HOOK` only ever holds null or a `Hook` stored by `set_hook`
+    (!hook.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), Hook>(hook) })
+}
+
+#[doc(hidden)]
+pub fn seen(type_name: &'static str, id: &Identifier) {
+    let hook = HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        // SAFETY: `HOOK` only ever holds null or a `Hook` stored by `set_hook`
+        let hook = unsafe { core::mem::transmute::<*mut (), Hook>(hook) };
+        hook(type_name, id);
+    }
+}
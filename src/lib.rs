@@ -1,14 +1,202 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// Lets `#[derive(EntityId)]` refer to `::entity_ident` from within this crate's tests.
+extern crate self as entity_ident;
+
+mod entity_id;
 mod identifier;
 
+pub mod legacy;
+
+#[cfg(feature = "registry")]
+pub mod registry;
+
+#[cfg(feature = "rusqlite")]
+pub mod sqlite;
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+#[cfg(feature = "prost")]
+pub mod proto;
+
+#[cfg(feature = "schemars")]
+pub mod json_schema;
+
+#[cfg(feature = "utoipa")]
+pub mod openapi;
+
+#[cfg(feature = "async-graphql")]
+pub mod graphql;
+
+#[cfg(feature = "axum")]
+pub mod axum;
+
+#[cfg(feature = "actix-web")]
+pub mod actix;
+
+#[cfg(feature = "clap")]
+pub mod cli;
+
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+pub mod testing;
+
+#[cfg(feature = "ts-rs")]
+pub mod typescript;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 #[cfg(test)]
 mod tests;
 
+pub use entity_id::EntityId;
 pub use identifier::Identifier;
 pub use identifier::InvalidIdentifierError;
 
+#[cfg(feature = "derive")]
+pub use entity_ident_derive::EntityId;
+
+#[cfg(feature = "registry")]
+pub use registry::AnyId;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::borrow::Cow;
+    pub use alloc::boxed::Box;
+    pub use alloc::string::String;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    pub use std::error::Error;
+    #[cfg(feature = "std")]
+    pub use std::path::PathBuf;
+    #[cfg(feature = "std")]
+    pub use std::sync::OnceLock;
+
+    pub use crate::identifier::is_valid_prefix;
+
+    /// Parses an id of the type `typename`, whose `prefixes` are listed in the error.
+    ///
+    /// This is the `FromStr` of both forms of `def_id!`, which then check the prefix with
+    /// `EntityId::from_identifier`, so that legacy prefixes are accepted without being listed.
+    pub fn parse_id(
+        s: &str,
+        typename: &'static str,
+        prefixes: &'static [&'static str],
+    ) -> Result<crate::Identifier, crate::ParseIdError> {
+        crate::Identifier::from_str(s).map_err(|_| crate::ParseIdError::new(typename, "a valid id").with_prefixes(prefixes))
+    }
+
+    /// The regular expression matched by ids with one of `prefixes`, or by any id if there are
+    /// none, as used by the JSON Schema and OpenAPI descriptions.
+    #[cfg(any(feature = "schemars", feature = "utoipa"))]
+    pub fn id_pattern(prefixes: &[&str]) -> String {
+        match prefixes {
+            [] => "^[a-zA-Z0-9]{1,8}_[a-zA-Z0-9]{1,22}$".into(),
+            _ => alloc::format!("^({})_[a-zA-Z0-9]{{1,22}}$", prefixes.join("|")),
+        }
+    }
+
+    /// Whether any prefix appears more than once in `prefixes`.
+    pub const fn has_duplicate_prefix(prefixes: &[&str]) -> bool {
+        let mut i = 0;
+        while i < prefixes.len() {
+            let mut j = i + 1;
+            while j < prefixes.len() {
+                let (a, b) = (prefixes[i].as_bytes(), prefixes[j].as_bytes());
+                if a.len() == b.len() {
+                    let mut k = 0;
+                    while k < a.len() && a[k] == b[k] {
+                        k += 1;
+                    }
+                    if k == a.len() {
+                        return true;
+                    }
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// The example id with `prefix` shown in the JSON Schema and OpenAPI descriptions, fixed so that
+    /// generated documents stay stable but shaped like a generated id.
+    #[cfg(any(feature = "schemars", feature = "utoipa"))]
+    pub fn example_id(prefix: &str) -> Option<crate::Identifier> {
+        crate::Identifier::from_str(&alloc::format!("{prefix}_C3M2XCLwa3LjkkH4V15muQ")).ok()
+    }
+
+    /// Concatenates the prefix lists of an enum's variants into one array of `N` prefixes.
+    pub const fn concat_prefixes<const N: usize>(parts: &[&[&'static str]]) -> [&'static str; N] {
+        let mut prefixes = [""; N];
+        let mut n = 0;
+        let mut i = 0;
+        while i < parts.len() {
+            let mut j = 0;
+            while j < parts[i].len() {
+                prefixes[n] = parts[i][j];
+                n += 1;
+                j += 1;
+            }
+            i += 1;
+        }
+        assert!(n == N, "prefix count mismatch");
+        prefixes
+    }
+
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    #[cfg(feature = "registry")]
+    pub use inventory;
+
+    #[cfg(feature = "rusqlite")]
+    pub use rusqlite;
+
+    #[cfg(feature = "postgres")]
+    pub use bytes;
+    #[cfg(feature = "postgres")]
+    pub use postgres_types;
+
+    #[cfg(feature = "schemars")]
+    pub use schemars;
+
+    #[cfg(feature = "utoipa")]
+    pub use utoipa;
+
+    #[cfg(feature = "async-graphql")]
+    pub use async_graphql;
+
+    #[cfg(feature = "clap")]
+    pub use clap;
+
+    #[cfg(feature = "arbitrary")]
+    pub use arbitrary;
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+    #[cfg(feature = "quickcheck")]
+    pub use quickcheck;
+
+    #[cfg(feature = "ts-rs")]
+    pub use ts_rs;
+
+    #[cfg(feature = "wasm")]
+    pub use wasm_bindgen;
+}
+
 #[macro_export]
 macro_rules! def_id {
-    ($struct_name:ident, $prefix:literal $(| $alt_prefix:literal)* $(, { $generate_hint:tt })?) => {
+    ($struct_name:ident, $prefix:literal $(| $alt_prefix:literal)* $(, legacy: $($legacy_prefix:literal)|+)? $(, { $generate_hint:tt })?) => {
         /// An id for the corresponding object type.
         ///
         /// This type _typically_ will not allocate and
@@ -17,105 +205,147 @@ macro_rules! def_id {
         #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
         pub struct $struct_name($crate::Identifier);
 
-        impl $struct_name {
-            /// The prefix of the id (e.g. `cus` for a `CustomerId`).
+        $crate::def_id_struct_impls!(pub $struct_name, $prefix $(| $alt_prefix)*, legacy [$($($legacy_prefix),+)?]);
+    };
+    (enum $enum_name:ident { $( $(#[$test:meta])? $variant_name:ident($($variant_type:tt)*) ),+ $(,)? }) => {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        pub enum $enum_name {
+            $( $(#[$test])* $variant_name($($variant_type)*), )*
+        }
+
+        impl $enum_name {
+            /// The valid prefixes of all the variants' id types.
             #[allow(dead_code)]
-            #[inline(always)]
-            pub fn prefix(&self) -> &str {
-                self.0.prefix()
-            }
+            pub const PREFIXES: &'static [&'static str] = {
+                const LEN: usize = 0 $( + <$($variant_type)*>::PREFIXES.len() )*;
+                const PREFIXES: [&str; LEN] = $crate::__private::concat_prefixes(&[$( <$($variant_type)*>::PREFIXES ),*]);
+                &PREFIXES
+            };
+
+            /// The legacy prefixes of all the variants' id types.
+            #[allow(dead_code)]
+            pub const LEGACY_PREFIXES: &'static [&'static str] = {
+                const LEN: usize = 0 $( + <$($variant_type)*>::LEGACY_PREFIXES.len() )*;
+                const PREFIXES: [&str; LEN] = $crate::__private::concat_prefixes(&[$( <$($variant_type)*>::LEGACY_PREFIXES ),*]);
+                &PREFIXES
+            };
 
+            /// The valid prefixes of all the variants' id types.
             #[allow(dead_code)]
             #[inline(always)]
-            pub fn default_prefix() -> &'static str {
-                $prefix
+            pub fn prefixes() -> &'static [&'static str] {
+                Self::PREFIXES
             }
 
-            /// The prefix of the id (e.g. `cus` for a `CustomerId`).
+            /// The name of the variant holding the id (e.g. `Charge`).
             #[allow(dead_code)]
-            #[inline(always)]
-            pub fn inner(&self) -> &$crate::Identifier {
-                &self.0
+            pub fn variant_name(&self) -> &'static str {
+                match *self {
+                    $( $enum_name::$variant_name(_) => stringify!($variant_name), )*
+                }
             }
 
-            /// The valid prefixes of the id type (e.g. [`ch`, `py`\ for a `ChargeId`).
+            /// The names of the variants' id types, in declaration order (e.g. `ChargeId`, `UserId`).
             #[allow(dead_code)]
-            #[inline(always)]
-            pub fn prefixes() -> &'static [&'static str] {
-                &[$prefix$(, $alt_prefix)*]
+            pub fn variant_type_names() -> impl Iterator<Item = &'static str> {
+                [$( stringify!($($variant_type)*) ),*].into_iter()
             }
 
-            /// Extracts a string slice containing the entire id.
+            /// The prefix of the id (e.g. `ch` for a `Charge` variant holding a `ChargeId`).
             #[allow(dead_code)]
-            #[inline(always)]
-            pub fn as_str(&self) -> &str {
-                self.0.as_str()
+            pub fn prefix(&self) -> &str {
+                match *self {
+                    $( $enum_name::$variant_name(ref id) => id.prefix(), )*
+                }
             }
 
-            /// Check is provided prefix would be a valid prefix for id's of this type
+            /// Check if the provided prefix is valid for any of the variants.
             #[allow(dead_code)]
             pub fn is_valid_prefix(prefix: &str) -> bool {
-                prefix == $prefix $( || prefix == $alt_prefix )*
+                $( <$($variant_type)*>::is_valid_prefix(prefix) )||*
             }
 
             #[allow(dead_code)]
             pub fn is_valid_prefix_bytes(prefix: &[u8]) -> bool {
-                prefix == $prefix.as_bytes() $( || prefix == $alt_prefix.as_bytes() )*
+                $( <$($variant_type)*>::is_valid_prefix_bytes(prefix) )||*
             }
 
+            /// Check if the provided prefix is a legacy prefix of any of the variants.
             #[allow(dead_code)]
-            pub fn generate() -> Result<Self, $crate::identifier::InvalidIdentifierError> {
-                Ok(Self($crate::Identifier::generate($prefix)?))
+            pub fn is_legacy_prefix(prefix: &str) -> bool {
+                $( <$($variant_type)*>::is_legacy_prefix(prefix) )||*
             }
 
+            /// Whether the id has a legacy prefix.
             #[allow(dead_code)]
-            pub fn from_bytes(bytes: &[u8]) -> Result<Self, $crate::identifier::InvalidIdentifierError> {
-                let prefix = bytes.split(|&b| b == b'_').next().ok_or($crate::identifier::InvalidIdentifierError)?;
+            pub fn is_legacy(&self) -> bool {
+                match *self {
+                    $( $enum_name::$variant_name(ref id) => id.is_legacy(), )*
+                }
+            }
 
-                if !Self::is_valid_prefix_bytes(prefix) {
-                    return Err($crate::identifier::InvalidIdentifierError);
+            /// The id with a legacy prefix replaced by its type's default one, keeping its payload.
+            #[allow(dead_code)]
+            pub fn canonicalize(self) -> Self {
+                match self {
+                    $( $enum_name::$variant_name(id) => $enum_name::$variant_name(id.canonicalize()), )*
                 }
-                Ok(Self($crate::Identifier::from_bytes(bytes)?))
             }
-        }
 
-        impl PartialEq<str> for $struct_name {
-            fn eq(&self, other: &str) -> bool {
-                self.as_str() == other
+            #[allow(dead_code)]
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $( $enum_name::$variant_name(ref id) => id.as_str(), )*
+                }
             }
-        }
 
-        impl PartialEq<&str> for $struct_name {
-            fn eq(&self, other: &&str) -> bool {
-                self.as_str() == *other
+            #[allow(dead_code)]
+            pub fn as_bytes(&self) -> &[u8] {
+                match *self {
+                    $( $enum_name::$variant_name(ref id) => id.as_bytes(), )*
+                }
+            }
+
+            #[allow(dead_code)]
+            pub fn inner(&self) -> &$crate::Identifier {
+                match *self {
+                    $( $enum_name::$variant_name(ref id) => id.inner(), )*
+                }
+            }
+
+            #[allow(dead_code)]
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, $crate::InvalidIdentifierError> {
+                // Validate once, then dispatch on the prefix without re-parsing in each variant.
+                let id = $crate::Identifier::from_bytes(bytes)?;
+                <Self as $crate::EntityId>::from_identifier(id).map_err(|_| $crate::InvalidIdentifierError)
             }
         }
 
-        impl PartialEq<String> for $struct_name {
-            fn eq(&self, other: &String) -> bool {
+        impl PartialEq<str> for $enum_name {
+            fn eq(&self, other: &str) -> bool {
                 self.as_str() == other
             }
         }
 
-        impl PartialOrd for $struct_name {
-            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-                Some(self.cmp(other))
+        impl PartialEq<&str> for $enum_name {
+            fn eq(&self, other: &&str) -> bool {
+                self.as_str() == *other
             }
         }
 
-        impl Ord for $struct_name {
-            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-                self.as_str().cmp(other.as_str())
+        impl PartialEq<$crate::__private::String> for $enum_name {
+            fn eq(&self, other: &$crate::__private::String) -> bool {
+                self.as_str() == other
             }
         }
 
-        impl AsRef<str> for $struct_name {
+        impl AsRef<str> for $enum_name {
             fn as_ref(&self) -> &str {
                 self.as_str()
             }
         }
 
-        impl std::ops::Deref for $struct_name {
+        impl ::core::ops::Deref for $enum_name {
             type Target = str;
 
             fn deref(&self) -> &str {
@@ -123,113 +353,259 @@ macro_rules! def_id {
             }
         }
 
-        impl std::fmt::Display for $struct_name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                std::fmt::Display::fmt(&self.0, f)
+        impl ::core::fmt::Display for $enum_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                match *self {
+                    $( $enum_name::$variant_name(ref id) => ::core::fmt::Display::fmt(&id, f), )*
+                }
             }
         }
 
-        impl std::str::FromStr for $struct_name {
-            type Err = $crate::ParseIdError;
+        impl $crate::EntityId for $enum_name {
+            const TYPE_NAME: &'static str = stringify!($enum_name);
+            const PREFIXES: &'static [&'static str] = $enum_name::PREFIXES;
+            const LEGACY_PREFIXES: &'static [&'static str] = $enum_name::LEGACY_PREFIXES;
+            // The first variant's default prefix, as `PREFIXES` lists the variants' prefixes in order.
+            const DEFAULT_PREFIX: &'static str = $enum_name::PREFIXES[0];
 
-            fn from_str(s: &str) -> Result<Self, Self::Err> {               
-                if !s.starts_with(concat!($prefix, "_")) $(
-                    && !s.starts_with(concat!($alt_prefix, "_"))
-                )* {
-                    Err($crate::ParseIdError {
-                        typename: stringify!($struct_name),
-                        expected: stringify!(id to start with $prefix $(or $alt_prefix)*),
-                    })
-                } else {
-                    match s.parse() {
-                        Ok(id) => Ok($struct_name(id)),
-                        Err(_) => Err($crate::ParseIdError {
-                            typename: stringify!($struct_name),
-                            expected: stringify!(invalid identifier),
-                        }),
+            fn inner(&self) -> &$crate::Identifier {
+                $enum_name::inner(self)
+            }
+
+            fn from_identifier(id: $crate::Identifier) -> Result<Self, $crate::ParseIdError> {
+                $(
+                    if <$($variant_type)*>::is_valid_prefix(id.prefix()) {
+                        return <$($variant_type)* as $crate::EntityId>::from_identifier(id).map($enum_name::$variant_name);
                     }
+                )*
+                Err($crate::ParseIdError::new(stringify!($enum_name), "an id").with_prefixes($enum_name::PREFIXES))
+            }
+
+            fn canonicalize(self) -> Self {
+                $enum_name::canonicalize(self)
+            }
+        }
+
+        impl ::core::str::FromStr for $enum_name {
+            type Err = $crate::ParseIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let id = $crate::__private::parse_id(s, stringify!($enum_name), Self::PREFIXES)?;
+                <Self as $crate::EntityId>::from_identifier(id)
+            }
+        }
+
+        impl ::core::convert::TryFrom<&str> for $enum_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl ::core::convert::TryFrom<$crate::__private::String> for $enum_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(s: $crate::__private::String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl From<$enum_name> for $crate::__private::String {
+            fn from(id: $enum_name) -> Self {
+                match id {
+                    $( $enum_name::$variant_name(id) => id.into(), )*
                 }
             }
         }
 
-        impl Into<String> for $struct_name {
-            fn into(self) -> String {
-                self.0.into()
+        impl ::core::convert::TryFrom<$crate::Identifier> for $enum_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(id: $crate::Identifier) -> Result<Self, Self::Error> {
+                <Self as $crate::EntityId>::from_identifier(id)
             }
         }
 
-        def_id_serde_impls!($struct_name);
-    };
-    (enum $enum_name:ident { $( $(#[$test:meta])? $variant_name:ident($($variant_type:tt)*) ),+ $(,)? }) => {
-        #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-        pub enum $enum_name {
-            $( $(#[$test])* $variant_name($($variant_type)*), )*
+        impl From<$enum_name> for $crate::Identifier {
+            fn from(id: $enum_name) -> Self {
+                *id.inner()
+            }
         }
 
-        impl $enum_name {
-            #[allow(dead_code)]
-            pub fn as_str(&self) -> &str {
-                match *self {
-                    $( $enum_name::$variant_name(ref id) => id.as_str(), )*
+        $(
+            impl From<$($variant_type)*> for $enum_name {
+                fn from(id: $($variant_type)*) -> Self {
+                    $enum_name::$variant_name(id)
                 }
             }
 
-            #[allow(dead_code)]
-            pub fn as_bytes(&self) -> &[u8] {
-                match *self {
-                    $( $enum_name::$variant_name(ref id) => id.as_bytes(), )*
+            impl PartialEq<$($variant_type)*> for $enum_name {
+                fn eq(&self, other: &$($variant_type)* ) -> bool {
+                    self.as_str() == other.as_str()
                 }
             }
+        )*
+
+        $crate::def_id_generate_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_serde_impls!($enum_name);
+        $crate::def_id_rusqlite_impls!(enum $enum_name);
+        $crate::def_id_postgres_impls!(enum $enum_name);
+        $crate::def_id_prost_impls!($enum_name);
+        $crate::def_id_schemars_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_utoipa_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_async_graphql_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_ts_rs_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_clap_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_arbitrary_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_proptest_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+        $crate::def_id_quickcheck_impls!(enum $enum_name { $( $variant_name($($variant_type)*) ),* });
+    };
+}
+
+/// The impls behind `def_id!` and `#[derive(EntityId)]` for a struct wrapping an `Identifier`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_struct_impls {
+    ($vis:vis $struct_name:ident, $prefix:literal $(| $alt_prefix:literal)*) => {
+        $crate::def_id_struct_impls!($vis $struct_name, $prefix $(| $alt_prefix)*, legacy []);
+    };
+    ($vis:vis $struct_name:ident, $prefix:literal $(| $alt_prefix:literal)*, legacy [$($legacy_prefix:literal),*]) => {
+        const _: () = {
+            $crate::def_id_assert_prefix!($prefix);
+            $( $crate::def_id_assert_prefix!($alt_prefix); )*
+            $( $crate::def_id_assert_prefix!($legacy_prefix); )*
+            assert!(
+                !$crate::__private::has_duplicate_prefix(&[$prefix $(, $alt_prefix)* $(, $legacy_prefix)*]),
+                "{}",
+                concat!("duplicate id prefix in `", stringify!($struct_name), "`, each prefix can only be listed once"),
+            );
+        };
+
+        impl $struct_name {
+            /// The prefix of the id (e.g. `cus` for a `CustomerId`).
+            #[allow(dead_code)]
+            #[inline(always)]
+            $vis fn prefix(&self) -> &str {
+                self.0.prefix()
+            }
 
             #[allow(dead_code)]
-            pub fn inner(&self) -> &Identifier {
-                match *self {
-                    $( $enum_name::$variant_name(ref id) => id.inner(), )*
-                }
+            #[inline(always)]
+            $vis fn default_prefix() -> &'static str {
+                $prefix
             }
 
+            /// The prefix of the id (e.g. `cus` for a `CustomerId`).
             #[allow(dead_code)]
-            pub fn from_bytes(bytes: &[u8]) -> Result<Self, $crate::identifier::InvalidIdentifierError> {
-                let prefix = bytes.split(|&b| b == b'_').next().ok_or($crate::identifier::InvalidIdentifierError)?;
+            #[inline(always)]
+            $vis fn inner(&self) -> &$crate::Identifier {
+                &self.0
+            }
 
-                // Check each variant to see if this is a valid prefix for that variant, by using the variant type
-                // to check the prefix.
-                $(
-                    if <$($variant_type)*>::is_valid_prefix_bytes(prefix) {
-                        return Ok($enum_name::$variant_name(<$($variant_type)*>::from_bytes(bytes)?));
-                    }
-                )*
+            /// The valid prefixes of the id type, starting with the default one.
+            #[allow(dead_code)]
+            $vis const PREFIXES: &'static [&'static str] = &[$prefix $(, $alt_prefix)*];
+
+            /// The legacy prefixes of the id type, which are parsed but never generated or listed in
+            /// `PREFIXES`.
+            #[allow(dead_code)]
+            $vis const LEGACY_PREFIXES: &'static [&'static str] = &[$($legacy_prefix),*];
+
+            /// The valid prefixes of the id type (e.g. [`ch`, `py`\ for a `ChargeId`).
+            #[allow(dead_code)]
+            #[inline(always)]
+            $vis fn prefixes() -> &'static [&'static str] {
+                Self::PREFIXES
+            }
+
+            /// Extracts a string slice containing the entire id.
+            #[allow(dead_code)]
+            #[inline(always)]
+            $vis fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
+
+            /// Check is provided prefix would be a valid prefix for id's of this type
+            #[allow(dead_code)]
+            $vis fn is_valid_prefix(prefix: &str) -> bool {
+                prefix == $prefix $( || prefix == $alt_prefix )* $( || prefix == $legacy_prefix )*
+            }
 
-                Err($crate::identifier::InvalidIdentifierError)
+            #[allow(dead_code)]
+            $vis fn is_valid_prefix_bytes(prefix: &[u8]) -> bool {
+                prefix == $prefix.as_bytes() $( || prefix == $alt_prefix.as_bytes() )* $( || prefix == $legacy_prefix.as_bytes() )*
+            }
+
+            /// Check if the provided prefix is one of the legacy prefixes of this type.
+            #[allow(dead_code)]
+            $vis fn is_legacy_prefix(prefix: &str) -> bool {
+                Self::LEGACY_PREFIXES.contains(&prefix)
+            }
 
+            /// Whether the id has a legacy prefix.
+            #[allow(dead_code)]
+            $vis fn is_legacy(&self) -> bool {
+                Self::is_legacy_prefix(self.prefix())
+            }
+
+            /// The id with a legacy prefix replaced by the default one, keeping its payload.
+            #[allow(dead_code)]
+            $vis fn canonicalize(self) -> Self {
+                if !self.is_legacy() {
+                    return self;
+                }
+                match self.0.with_prefix($prefix) {
+                    Ok(id) => Self(id),
+                    // The prefix is checked at compile time by `def_id_assert_prefix!`
+                    Err(_) => unreachable!(),
+                }
+            }
+
+            #[allow(dead_code)]
+            $vis fn from_bytes(bytes: &[u8]) -> Result<Self, $crate::InvalidIdentifierError> {
+                let id = $crate::Identifier::from_bytes(bytes)?;
+                <Self as $crate::EntityId>::from_identifier(id).map_err(|_| $crate::InvalidIdentifierError)
             }
         }
 
-        impl PartialEq<str> for $enum_name {
+        impl PartialEq<str> for $struct_name {
             fn eq(&self, other: &str) -> bool {
                 self.as_str() == other
             }
         }
 
-        impl PartialEq<&str> for $enum_name {
+        impl PartialEq<&str> for $struct_name {
             fn eq(&self, other: &&str) -> bool {
                 self.as_str() == *other
             }
         }
 
-        impl PartialEq<String> for $enum_name {
-            fn eq(&self, other: &String) -> bool {
+        impl PartialEq<$crate::__private::String> for $struct_name {
+            fn eq(&self, other: &$crate::__private::String) -> bool {
                 self.as_str() == other
             }
         }
 
-        impl AsRef<str> for $enum_name {
+        impl PartialOrd for $struct_name {
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $struct_name {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                self.as_str().cmp(other.as_str())
+            }
+        }
+
+        impl AsRef<str> for $struct_name {
             fn as_ref(&self) -> &str {
                 self.as_str()
             }
         }
 
-        impl std::ops::Deref for $enum_name {
+        impl ::core::ops::Deref for $struct_name {
             type Target = str;
 
             fn deref(&self) -> &str {
@@ -237,73 +613,180 @@ macro_rules! def_id {
             }
         }
 
-        impl std::fmt::Display for $enum_name {
-            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                match *self {
-                    $( $enum_name::$variant_name(ref id) => std::fmt::Display::fmt(&id, f), )*
+        impl ::core::fmt::Display for $struct_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl $crate::EntityId for $struct_name {
+            const TYPE_NAME: &'static str = stringify!($struct_name);
+            const PREFIXES: &'static [&'static str] = &[$prefix $(, $alt_prefix)*];
+            const LEGACY_PREFIXES: &'static [&'static str] = &[$($legacy_prefix),*];
+            const DEFAULT_PREFIX: &'static str = $prefix;
+
+            fn inner(&self) -> &$crate::Identifier {
+                &self.0
+            }
+
+            fn from_identifier(id: $crate::Identifier) -> Result<Self, $crate::ParseIdError> {
+                if !$struct_name::is_valid_prefix(id.prefix()) {
+                    return Err($crate::ParseIdError::new(stringify!($struct_name), "an id").with_prefixes($struct_name::PREFIXES));
+                }
+                if $struct_name::is_legacy_prefix(id.prefix()) {
+                    $crate::legacy::seen(stringify!($struct_name), &id);
                 }
+                Ok($struct_name(id))
+            }
+
+            fn canonicalize(self) -> Self {
+                $struct_name::canonicalize(self)
             }
         }
 
-        impl std::str::FromStr for $enum_name {
+        impl ::core::str::FromStr for $struct_name {
             type Err = $crate::ParseIdError;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                let prefix = s.find('_')
-                    .map(|i| &s[0..=i])
-                    .ok_or_else(|| $crate::ParseIdError {
-                        typename: stringify!($enum_name),
-                        expected: "id to start with a prefix (as in 'prefix_')"
-                    })?;
-
-                match prefix {
-                    $(_ if $($variant_type)*::is_valid_prefix(prefix) => {
-                        Ok($enum_name::$variant_name(s.parse()?))
-                    })*
-                    _ => {
-                        Err($crate::ParseIdError {
-                            typename: stringify!($enum_name),
-                            expected: "unknown id prefix",
-                        })
-                    }
-                }
+                let id = $crate::__private::parse_id(s, stringify!($struct_name), Self::PREFIXES)?;
+                <Self as $crate::EntityId>::from_identifier(id)
             }
         }
 
-        impl Into<String> for $enum_name {
-            fn into(self) -> String {
-                match self {
-                    $( $enum_name::$variant_name(id) => id.into(), )*
-                }
+        impl ::core::convert::TryFrom<&str> for $struct_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                s.parse()
             }
         }
 
-        $(
-            impl From<$($variant_type)*> for $enum_name {
-                fn from(id: $($variant_type)*) -> Self {
-                    $enum_name::$variant_name(id)
+        impl ::core::convert::TryFrom<$crate::__private::String> for $struct_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(s: $crate::__private::String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl From<$struct_name> for $crate::__private::String {
+            fn from(id: $struct_name) -> Self {
+                id.0.into()
+            }
+        }
+
+        impl ::core::convert::TryFrom<$crate::Identifier> for $struct_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(id: $crate::Identifier) -> Result<Self, Self::Error> {
+                <Self as $crate::EntityId>::from_identifier(id)
+            }
+        }
+
+        impl From<$struct_name> for $crate::Identifier {
+            fn from(id: $struct_name) -> Self {
+                id.0
+            }
+        }
+
+        $crate::def_id_generate_impls!($vis $struct_name, $prefix);
+        $crate::def_id_registry_impls!($struct_name, $prefix $(| $alt_prefix)*, legacy [$($legacy_prefix),*]);
+        $crate::def_id_serde_impls!($struct_name);
+        $crate::def_id_rusqlite_impls!($struct_name);
+        $crate::def_id_postgres_impls!($struct_name);
+        $crate::def_id_prost_impls!($struct_name);
+        $crate::def_id_schemars_impls!($struct_name);
+        $crate::def_id_utoipa_impls!($struct_name);
+        $crate::def_id_async_graphql_impls!($struct_name);
+        $crate::def_id_ts_rs_impls!($struct_name, $prefix $(| $alt_prefix)*);
+        $crate::def_id_clap_impls!($struct_name);
+        $crate::def_id_arbitrary_impls!($struct_name);
+        $crate::def_id_proptest_impls!($struct_name);
+        $crate::def_id_quickcheck_impls!($struct_name);
+    };
+}
+
+/// Fails compilation if `$prefix` is not 1 to 8 ASCII alphanumerics.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_assert_prefix {
+    ($prefix:literal) => {
+        assert!(
+            $crate::__private::is_valid_prefix($prefix.as_bytes()),
+            "{}",
+            concat!("invalid id prefix `", $prefix, "`, prefixes must be 1 to 8 ASCII letters or digits"),
+        );
+    };
+}
+
+#[cfg(feature = "getrandom")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_generate_impls {
+    ($vis:vis $struct_name:ident, $prefix:literal) => {
+        impl $struct_name {
+            /// Generates a new id with the default prefix.
+            #[allow(dead_code)]
+            $vis fn generate() -> Self {
+                match $crate::Identifier::generate($prefix) {
+                    Ok(id) => Self(id),
+                    // The prefix is checked at compile time by `def_id_assert_prefix!`
+                    Err(_) => unreachable!(),
                 }
             }
 
-            impl PartialEq<$($variant_type)*> for $enum_name {
-                fn eq(&self, other: &$($variant_type)* ) -> bool {
-                    self.as_str() == other.as_str()
+            /// Generates a new id with `prefix`, or returns `None` if it is not one of `prefixes()` or
+            /// is a legacy prefix.
+            #[allow(dead_code)]
+            $vis fn generate_with_prefix(prefix: &str) -> Option<Self> {
+                if !Self::is_valid_prefix(prefix) || Self::is_legacy_prefix(prefix) {
+                    return None;
+                }
+                $crate::Identifier::generate(prefix).ok().map(Self)
+            }
+        }
+    };
+    (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+        impl $enum_name {
+            /// Generates a new id of the first variant, with its default prefix.
+            #[allow(dead_code)]
+            pub fn generate() -> Self {
+                match Self::generate_with_prefix(<Self as $crate::EntityId>::DEFAULT_PREFIX) {
+                    Some(id) => id,
+                    None => unreachable!("the default prefix is accepted by the first variant"),
                 }
             }
-        )*
 
-        def_id_serde_impls!($enum_name);
+            /// Generates a new id of the variant that accepts `prefix`, or returns `None` if no
+            /// variant does.
+            #[allow(dead_code)]
+            pub fn generate_with_prefix(prefix: &str) -> Option<Self> {
+                $(
+                    if let Some(id) = <$($variant_type)*>::generate_with_prefix(prefix) {
+                        return Some($enum_name::$variant_name(id));
+                    }
+                )*
+                None
+            }
+        }
     };
 }
 
+#[cfg(not(feature = "getrandom"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_generate_impls {
+    ($($tt:tt)*) => {};
+}
+
 #[cfg(feature = "serde")]
 #[macro_export]
 macro_rules! def_id_serde_impls {
     ($struct_name:ident) => {
-        impl serde::Serialize for $struct_name {
+        impl $crate::__private::serde::Serialize for $struct_name {
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             where
-                S: serde::ser::Serializer,
+                S: $crate::__private::serde::ser::Serializer,
             {
                 if serializer.is_human_readable() {
                     serializer.serialize_str(self.as_str())
@@ -313,17 +796,17 @@ macro_rules! def_id_serde_impls {
             }
         }
 
-        impl<'de> serde::Deserialize<'de> for $struct_name {
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $struct_name {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
             where
-                D: serde::de::Deserializer<'de>,
+                D: $crate::__private::serde::de::Deserializer<'de>,
             {
                 if deserializer.is_human_readable() {
-                    let s: String = serde::Deserialize::deserialize(deserializer)?;
-                    s.parse::<Self>().map_err(::serde::de::Error::custom)
+                    let s: $crate::__private::String = $crate::__private::serde::Deserialize::deserialize(deserializer)?;
+                    s.parse::<Self>().map_err($crate::__private::serde::de::Error::custom)
                 } else {
-                    let b: &[u8] = serde::Deserialize::deserialize(deserializer)?;
-                    Self::from_bytes(b).map_err(::serde::de::Error::custom)
+                    let b: &[u8] = $crate::__private::serde::Deserialize::deserialize(deserializer)?;
+                    Self::from_bytes(b).map_err($crate::__private::serde::de::Error::custom)
                 }
             }
         }
@@ -337,21 +820,181 @@ macro_rules! def_id_serde_impls {
     ($struct_name:ident) => {};
 }
 
+#[cfg(not(feature = "registry"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_registry_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "rusqlite"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_rusqlite_impls {
+    ($struct_name:ident) => {};
+    (enum $enum_name:ident) => {};
+}
+
+#[cfg(not(feature = "postgres"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_postgres_impls {
+    ($struct_name:ident) => {};
+    (enum $enum_name:ident) => {};
+}
+
+#[cfg(not(feature = "prost"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_prost_impls {
+    ($struct_name:ident) => {};
+}
+
+#[cfg(not(feature = "schemars"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_schemars_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "utoipa"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_utoipa_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "async-graphql"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_async_graphql_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "ts-rs"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_ts_rs_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "clap"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_clap_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "arbitrary"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_arbitrary_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "proptest"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_proptest_impls {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "quickcheck"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_quickcheck_impls {
+    ($($tt:tt)*) => {};
+}
+
 
 #[derive(Clone, Debug)]
 pub struct ParseIdError {
     typename: &'static str,
     expected: &'static str,
+    prefixes: &'static [&'static str],
+}
+
+impl ParseIdError {
+    #[doc(hidden)]
+    pub const fn new(typename: &'static str, expected: &'static str) -> Self {
+        ParseIdError {
+            typename,
+            expected,
+            prefixes: &[],
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_prefixes(self, prefixes: &'static [&'static str]) -> Self {
+        ParseIdError { prefixes, ..self }
+    }
+
+    /// The name of the id type that failed to parse (e.g. `UserId`).
+    pub fn typename(&self) -> &'static str {
+        self.typename
+    }
+
+    /// What the id was expected to look like.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// The prefixes accepted by the id type, if known.
+    pub fn prefixes(&self) -> &'static [&'static str] {
+        self.prefixes
+    }
+
+    /// The JSON body returned by web framework integrations when an id fails to parse.
+    #[cfg(any(feature = "axum", feature = "actix-web"))]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": "invalid_id",
+            "type": self.typename,
+            "expected": self.expected,
+            "prefixes": self.prefixes,
+            "message": self.to_string(),
+        })
+    }
 }
 
-impl std::fmt::Display for ParseIdError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "invalid `{}`, {}", self.typename, self.expected)
+impl core::fmt::Display for ParseIdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid `{}`, expected {}", self.typename, self.expected)?;
+        for (i, prefix) in self.prefixes.iter().enumerate() {
+            let separator = match i {
+                0 => " starting with ",
+                _ if i == self.prefixes.len() - 1 => " or ",
+                _ => ", ",
+            };
+            write!(f, "{separator}`{prefix}_`")?;
+        }
+        Ok(())
     }
 }
 
-impl std::error::Error for ParseIdError {
+impl core::error::Error for ParseIdError {
     fn description(&self) -> &str {
         "error parsing an id"
     }
 }
+
+/// The path or query parameter holding an id of the type `type_name` (e.g. `charge_id` for
+/// `ChargeId`).
+#[cfg(any(feature = "axum", feature = "actix-web", feature = "utoipa"))]
+pub(crate) fn param_name(type_name: &str) -> String {
+    let mut name = String::with_capacity(type_name.len() + 4);
+    for (i, c) in type_name.char_indices() {
+        if c.is_ascii_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+#[cfg(feature = "tonic")]
+impl From<ParseIdError> for tonic::Status {
+    fn from(err: ParseIdError) -> Self {
+        tonic::Status::invalid_argument(err.to_string())
+    }
+}
@@ -0,0 +1,127 @@
+//! OpenAPI support via `utoipa`.
+//!
+//! `def_id!` types are documented as strings matching their own prefixes, and `def_id!(enum ...)`
+//! unions as a `oneOf` of their variants. Id types also implement `IntoParams`, describing a single
+//! required parameter named after the type (`UserId` becomes `user_id`), so a handler for
+//! `/users/{user_id}` can list `params(UserId)`.
+
+use std::borrow::Cow;
+
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::schema::{ObjectBuilder, OneOfBuilder, Schema, Type};
+use utoipa::openapi::{Ref, RefOr, Required};
+use utoipa::{PartialSchema, ToSchema};
+
+use crate::Identifier;
+
+impl PartialSchema for Identifier {
+    fn schema() -> RefOr<Schema> {
+        id_schema(&crate::__private::id_pattern(&[]), None)
+    }
+}
+
+impl ToSchema for Identifier {
+    fn name() -> Cow<'static, str> {
+        "Identifier".into()
+    }
+}
+
+/// The schema of an id matching `pattern`, with an optional example.
+#[doc(hidden)]
+pub fn id_schema(pattern: &str, example: Option<&str>) -> RefOr<Schema> {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .pattern(Some(pattern))
+        .max_length(Some(31))
+        .examples(example)
+        .into()
+}
+
+/// A `oneOf` referencing each of the named variant schemas.
+#[doc(hidden)]
+pub fn one_of_schema(variants: &[Cow<'static, str>]) -> RefOr<Schema> {
+    variants
+        .iter()
+        .fold(OneOfBuilder::new(), |one_of, name| one_of.item(Ref::from_schema_name(name.as_ref())))
+        .into()
+}
+
+/// A single required parameter holding an id of type `type_name`.
+#[doc(hidden)]
+pub fn id_param(type_name: &str, parameter_in: Option<ParameterIn>, schema: RefOr<Schema>) -> Parameter {
+    ParameterBuilder::new()
+        .name(crate::param_name(type_name))
+        .parameter_in(parameter_in.unwrap_or(ParameterIn::Path))
+        .required(Required::True)
+        .schema(Some(schema))
+        .build()
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_utoipa_impls {
+    ($struct_name:ident) => {
+        impl $crate::__private::utoipa::PartialSchema for $struct_name {
+            fn schema() -> $crate::__private::utoipa::openapi::RefOr<$crate::__private::utoipa::openapi::schema::Schema> {
+                let example = $crate::__private::example_id(Self::default_prefix());
+                $crate::openapi::id_schema(
+                    &$crate::__private::id_pattern(Self::PREFIXES),
+                    example.as_ref().map($crate::Identifier::as_str),
+                )
+            }
+        }
+
+        impl $crate::__private::utoipa::ToSchema for $struct_name {
+            fn name() -> $crate::__private::Cow<'static, str> {
+                stringify!($struct_name).into()
+            }
+        }
+
+        $crate::def_id_utoipa_impls!(@params $struct_name);
+    };
+    (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+        impl $crate::__private::utoipa::PartialSchema for $enum_name {
+            fn schema() -> $crate::__private::utoipa::openapi::RefOr<$crate::__private::utoipa::openapi::schema::Schema> {
+                $crate::openapi::one_of_schema(&[
+                    $( <$($variant_type)* as $crate::__private::utoipa::ToSchema>::name(), )*
+                ])
+            }
+        }
+
+        impl $crate::__private::utoipa::ToSchema for $enum_name {
+            fn name() -> $crate::__private::Cow<'static, str> {
+                stringify!($enum_name).into()
+            }
+
+            fn schemas(
+                schemas: &mut $crate::__private::Vec<(
+                    $crate::__private::String,
+                    $crate::__private::utoipa::openapi::RefOr<$crate::__private::utoipa::openapi::schema::Schema>,
+                )>,
+            ) {
+                $(
+                    schemas.push((
+                        <$($variant_type)* as $crate::__private::utoipa::ToSchema>::name().into(),
+                        <$($variant_type)* as $crate::__private::utoipa::PartialSchema>::schema(),
+                    ));
+                    <$($variant_type)* as $crate::__private::utoipa::ToSchema>::schemas(schemas);
+                )*
+            }
+        }
+
+        $crate::def_id_utoipa_impls!(@params $enum_name);
+    };
+    (@params $name:ident) => {
+        impl $crate::__private::utoipa::IntoParams for $name {
+            fn into_params(
+                parameter_in_provider: impl Fn() -> Option<$crate::__private::utoipa::openapi::path::ParameterIn>,
+            ) -> $crate::__private::Vec<$crate::__private::utoipa::openapi::path::Parameter> {
+                $crate::__private::vec![$crate::openapi::id_param(
+                    stringify!($name),
+                    parameter_in_provider(),
+                    <Self as $crate::__private::utoipa::PartialSchema>::schema(),
+                )]
+            }
+        }
+    };
+}
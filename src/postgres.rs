@@ -0,0 +1,194 @@
+//! `postgres-types` support.
+//!
+//! Ids can be bound to and read from `text` and `varchar` columns. `def_id!` struct types can also
+//! use `uuid` columns, which hold only the 128-bit payload: the type's default prefix is restored
+//! when reading, so writing an id with any other prefix to a `uuid` column is an error.
+
+use std::error::Error;
+
+use bytes::BytesMut;
+use postgres_types::{FromSql, IsNull, ToSql, Type, WrongType};
+
+use crate::{Identifier, InvalidIdentifierError};
+
+impl ToSql for Identifier {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        to_sql(self.as_str(), ty, None, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts(ty, false)
+    }
+
+    postgres_types::to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Identifier {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        from_sql(ty, raw, None, Identifier::from_str)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts(ty, false)
+    }
+}
+
+/// Whether an id can be stored in a column of type `ty`.
+#[doc(hidden)]
+pub fn accepts(ty: &Type, uuid: bool) -> bool {
+    match *ty {
+        Type::TEXT | Type::VARCHAR => true,
+        Type::UUID => uuid,
+        _ => false,
+    }
+}
+
+/// Writes an id as text, or as its payload to a uuid column if it has the type's default prefix.
+#[doc(hidden)]
+pub fn to_sql(
+    id: &str,
+    ty: &Type,
+    default_prefix: Option<&str>,
+    out: &mut BytesMut,
+) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+    if *ty == Type::UUID {
+        let id = Identifier::from_str(id)?;
+        if Some(id.prefix()) != default_prefix {
+            return Err(match default_prefix {
+                Some(prefix) => format!("cannot store `{id}` in a uuid column, only ids starting with `{prefix}_` can be"),
+                None => format!("cannot store `{id}` in a uuid column"),
+            }
+            .into());
+        }
+        let payload = id.payload_bytes().ok_or(InvalidIdentifierError)?;
+        out.extend_from_slice(&payload);
+    } else {
+        out.extend_from_slice(id.as_bytes());
+    }
+    Ok(IsNull::No)
+}
+
+/// `ToSql::to_sql_checked` for `def_id!` types, which `postgres_types::to_sql_checked!` would
+/// expand to with `std` paths.
+#[doc(hidden)]
+pub fn to_sql_checked<T: ToSql>(id: &T, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+    if !T::accepts(ty) {
+        return Err(Box::new(WrongType::new::<T>(ty.clone())));
+    }
+    id.to_sql(ty, out)
+}
+
+/// Reads an id from text, or from a uuid when the type has a default prefix to restore.
+#[doc(hidden)]
+pub fn from_sql<T, E>(
+    ty: &Type,
+    raw: &[u8],
+    default_prefix: Option<&str>,
+    parse: fn(&str) -> Result<T, E>,
+) -> Result<T, Box<dyn Error + Sync + Send>>
+where
+    E: Error + Sync + Send + 'static,
+{
+    match (ty, default_prefix) {
+        (&Type::UUID, Some(prefix)) => {
+            let payload: [u8; 16] = raw.try_into()?;
+            let id = Identifier::from_payload(prefix, payload)?;
+            Ok(parse(id.as_str())?)
+        }
+        _ => Ok(parse(std::str::from_utf8(raw)?)?),
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_postgres_impls {
+    ($struct_name:ident) => {
+        impl $crate::__private::postgres_types::ToSql for $struct_name {
+            fn to_sql(
+                &self,
+                ty: &$crate::__private::postgres_types::Type,
+                out: &mut $crate::__private::bytes::BytesMut,
+            ) -> ::core::result::Result<
+                $crate::__private::postgres_types::IsNull,
+                $crate::__private::Box<dyn $crate::__private::Error + Sync + Send>,
+            > {
+                $crate::postgres::to_sql(self.as_str(), ty, Some(Self::default_prefix()), out)
+            }
+
+            fn accepts(ty: &$crate::__private::postgres_types::Type) -> bool {
+                $crate::postgres::accepts(ty, true)
+            }
+
+            fn to_sql_checked(
+                &self,
+                ty: &$crate::__private::postgres_types::Type,
+                out: &mut $crate::__private::bytes::BytesMut,
+            ) -> ::core::result::Result<
+                $crate::__private::postgres_types::IsNull,
+                $crate::__private::Box<dyn $crate::__private::Error + Sync + Send>,
+            > {
+                $crate::postgres::to_sql_checked(self, ty, out)
+            }
+        }
+
+        impl<'a> $crate::__private::postgres_types::FromSql<'a> for $struct_name {
+            fn from_sql(
+                ty: &$crate::__private::postgres_types::Type,
+                raw: &'a [u8],
+            ) -> ::core::result::Result<Self, $crate::__private::Box<dyn $crate::__private::Error + Sync + Send>> {
+                $crate::postgres::from_sql(
+                    ty,
+                    raw,
+                    Some(Self::default_prefix()),
+                    <Self as ::core::str::FromStr>::from_str,
+                )
+            }
+
+            fn accepts(ty: &$crate::__private::postgres_types::Type) -> bool {
+                $crate::postgres::accepts(ty, true)
+            }
+        }
+    };
+    (enum $enum_name:ident) => {
+        impl $crate::__private::postgres_types::ToSql for $enum_name {
+            fn to_sql(
+                &self,
+                ty: &$crate::__private::postgres_types::Type,
+                out: &mut $crate::__private::bytes::BytesMut,
+            ) -> ::core::result::Result<
+                $crate::__private::postgres_types::IsNull,
+                $crate::__private::Box<dyn $crate::__private::Error + Sync + Send>,
+            > {
+                $crate::postgres::to_sql(self.as_str(), ty, None, out)
+            }
+
+            fn accepts(ty: &$crate::__private::postgres_types::Type) -> bool {
+                $crate::postgres::accepts(ty, false)
+            }
+
+            fn to_sql_checked(
+                &self,
+                ty: &$crate::__private::postgres_types::Type,
+                out: &mut $crate::__private::bytes::BytesMut,
+            ) -> ::core::result::Result<
+                $crate::__private::postgres_types::IsNull,
+                $crate::__private::Box<dyn $crate::__private::Error + Sync + Send>,
+            > {
+                $crate::postgres::to_sql_checked(self, ty, out)
+            }
+        }
+
+        impl<'a> $crate::__private::postgres_types::FromSql<'a> for $enum_name {
+            fn from_sql(
+                ty: &$crate::__private::postgres_types::Type,
+                raw: &'a [u8],
+            ) -> ::core::result::Result<Self, $crate::__private::Box<dyn $crate::__private::Error + Sync + Send>> {
+                $crate::postgres::from_sql(ty, raw, None, <Self as ::core::str::FromStr>::from_str)
+            }
+
+            fn accepts(ty: &$crate::__private::postgres_types::Type) -> bool {
+                $crate::postgres::accepts(ty, false)
+            }
+        }
+    };
+}
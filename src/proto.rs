@@ -0,0 +1,51 @@
+//! Protobuf support via `prost`.
+//!
+//! Ids are usually carried as plain `string` fields and converted with `TryFrom<String>`. For
+//! APIs that want a dedicated type, [`Identifier`] is the `entity_ident.Identifier` message from
+//! `proto/entity_ident.proto`.
+
+/// The `entity_ident.Identifier` protobuf message.
+#[derive(Clone, PartialEq, Eq, Hash, prost::Message)]
+pub struct Identifier {
+    #[prost(string, tag = "1")]
+    pub value: String,
+}
+
+impl prost::Name for Identifier {
+    const NAME: &'static str = "Identifier";
+    const PACKAGE: &'static str = "entity_ident";
+}
+
+impl From<crate::Identifier> for Identifier {
+    fn from(id: crate::Identifier) -> Self {
+        Identifier { value: id.into() }
+    }
+}
+
+impl TryFrom<Identifier> for crate::Identifier {
+    type Error = crate::InvalidIdentifierError;
+
+    fn try_from(message: Identifier) -> Result<Self, Self::Error> {
+        crate::Identifier::from_str(&message.value)
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_prost_impls {
+    ($struct_name:ident) => {
+        impl From<$struct_name> for $crate::proto::Identifier {
+            fn from(id: $struct_name) -> Self {
+                $crate::proto::Identifier { value: id.into() }
+            }
+        }
+
+        impl TryFrom<$crate::proto::Identifier> for $struct_name {
+            type Error = $crate::ParseIdError;
+
+            fn try_from(message: $crate::proto::Identifier) -> Result<Self, Self::Error> {
+                message.value.parse()
+            }
+        }
+    };
+}
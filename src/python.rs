@@ -0,0 +1,82 @@
+//! Python bindings through `pyo3`.
+//!
+//! The `entity_ident` Python module exposes an `Identifier` class that parses, validates and
+//! generates ids with the same rules as the Rust types. Build it with `maturin`, which compiles the
+//! `entity_ident_py` wrapper crate (see `pyproject.toml`), or add [`entity_ident`] to your own
+//! `#[pymodule]`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{Identifier, InvalidIdentifierError};
+
+impl From<InvalidIdentifierError> for PyErr {
+    fn from(err: InvalidIdentifierError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// An id, e.g. `Identifier("user_C3M2XCLwa3LjkkH4V15muQ")`.
+///
+/// Ids are hashable and sort like their string form.
+#[pyclass(name = "Identifier", module = "entity_ident", frozen, eq, ord, hash, str)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Hash)]
+pub struct PyIdentifier(pub Identifier);
+
+#[pymethods]
+impl PyIdentifier {
+    #[new]
+    fn new(id: &str) -> PyResult<Self> {
+        Self::parse(id)
+    }
+
+    /// Parses an id, raising `ValueError` if it is invalid.
+    #[staticmethod]
+    pub fn parse(id: &str) -> PyResult<Self> {
+        Ok(PyIdentifier(Identifier::from_str(id)?))
+    }
+
+    /// Whether `id` is a valid id, without raising.
+    #[staticmethod]
+    pub fn is_valid(id: &str) -> bool {
+        Identifier::from_str(id).is_ok()
+    }
+
+    /// Generates a new id with the given prefix.
+    #[staticmethod]
+    pub fn generate(prefix: &str) -> PyResult<Self> {
+        Ok(PyIdentifier(Identifier::generate(prefix)?))
+    }
+
+    #[getter]
+    pub fn prefix(&self) -> &str {
+        self.0.prefix()
+    }
+
+    #[getter]
+    pub fn payload(&self) -> &str {
+        self.0.payload()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Identifier('{}')", self.0)
+    }
+}
+
+impl std::fmt::Display for PyIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Identifier> for PyIdentifier {
+    fn from(id: Identifier) -> Self {
+        PyIdentifier(id)
+    }
+}
+
+/// The `entity_ident` Python module.
+#[pymodule]
+pub fn entity_ident(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyIdentifier>()
+}
@@ -0,0 +1,363 @@
+//! A process-wide registry of `def_id!` types and their prefixes.
+//!
+//! Every struct declared with `def_id!` or `#[derive(EntityId)]` in any crate linked into the
+//! binary registers itself through `inventory`. This makes it possible to parse an arbitrary id
+//! into an [`AnyId`] that knows its type, and to catch two types claiming the same prefix:
+//!
+//! ```ignore
+//! #[test]
+//! fn id_prefixes_are_unique() {
+//!     entity_ident::registry::assert_no_collisions();
+//! }
+//! ```
+
+use core::any::TypeId;
+use core::str::FromStr;
+
+use alloc::vec::Vec;
+
+use crate::{EntityId, Identifier, InvalidIdentifierError, ParseIdError};
+
+/// A `def_id!` type and the prefixes it accepts.
+#[derive(Clone, Copy, Debug)]
+pub struct Registration {
+    type_name: &'static str,
+    module_path: &'static str,
+    prefixes: &'static [&'static str],
+    legacy_prefixes: &'static [&'static str],
+    type_id: fn() -> TypeId,
+    from_identifier: fn(Identifier) -> Result<Identifier, ParseIdError>,
+    canonicalize: fn(Identifier) -> Identifier,
+}
+
+impl Registration {
+    /// `from_identifier` and `canonicalize` are those of the id type, going through an
+    /// `Identifier` so that any id type can be registered.
+    #[doc(hidden)]
+    pub const fn new(
+        type_name: &'static str,
+        module_path: &'static str,
+        prefixes: &'static [&'static str],
+        legacy_prefixes: &'static [&'static str],
+        type_id: fn() -> TypeId,
+        from_identifier: fn(Identifier) -> Result<Identifier, ParseIdError>,
+        canonicalize: fn(Identifier) -> Identifier,
+    ) -> Self {
+        Registration {
+            type_name,
+            module_path,
+            prefixes,
+            legacy_prefixes,
+            type_id,
+            from_identifier,
+            canonicalize,
+        }
+    }
+
+    /// The name of the id type (e.g. `ChargeId`).
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The module the id type was declared in (e.g. `payments::ids`).
+    pub fn module_path(&self) -> &'static str {
+        self.module_path
+    }
+
+    /// The prefixes of the id type, starting with the default one.
+    pub fn prefixes(&self) -> &'static [&'static str] {
+        self.prefixes
+    }
+
+    /// The legacy prefixes the id type still parses.
+    pub fn legacy_prefixes(&self) -> &'static [&'static str] {
+        self.legacy_prefixes
+    }
+
+    /// Whether the id type parses ids starting with `prefix`, legacy or not.
+    pub fn accepts(&self, prefix: &str) -> bool {
+        self.prefixes.contains(&prefix) || self.legacy_prefixes.contains(&prefix)
+    }
+
+    /// Whether this is the registration of the id type `T`.
+    pub fn is<T: 'static>(&self) -> bool {
+        (self.type_id)() == TypeId::of::<T>()
+    }
+}
+
+inventory::collect!(Registration);
+
+/// A prefix claimed by more than one id type.
+#[derive(Clone, Debug)]
+pub struct Collision {
+    pub prefix: &'static str,
+    pub types: Vec<&'static Registration>,
+}
+
+/// Every registered id type, in no particular order.
+pub fn all() -> impl Iterator<Item = &'static Registration> {
+    inventory::iter::<Registration>.into_iter()
+}
+
+/// The id type that accepts `prefix`, including as a legacy prefix.
+///
+/// Returns `None` if no type or several types claim the prefix; see [`collisions`].
+pub fn lookup(prefix: &str) -> Option<&'static Registration> {
+    find(all(), prefix).ok()
+}
+
+/// The only registration accepting `prefix`, so that the result doesn't depend on link order.
+pub(crate) fn find(
+    registrations: impl Iterator<Item = &'static Registration>,
+    prefix: &str,
+) -> Result<&'static Registration, ParseIdError> {
+    let mut claims = registrations.filter(|registration| registration.accepts(prefix));
+    match (claims.next(), claims.next()) {
+        (Some(registration), None) => Ok(registration),
+        (None, _) => Err(ParseIdError::new("AnyId", "an id with a registered prefix")),
+        (Some(_), Some(_)) => Err(ParseIdError::new("AnyId", "an id whose prefix is registered by a single type")),
+    }
+}
+
+/// The prefixes, legacy ones included, claimed by more than one registered id type.
+pub fn collisions() -> Vec<Collision> {
+    find_collisions(all())
+}
+
+pub(crate) fn find_collisions(registrations: impl Iterator<Item = &'static Registration>) -> Vec<Collision> {
+    let mut claims: Vec<(&'static str, &'static Registration)> = registrations
+        .flat_map(|registration| {
+            let prefixes = registration.prefixes.iter().chain(registration.legacy_prefixes);
+            prefixes.map(move |&prefix| (prefix, registration))
+        })
+        .collect();
+    claims.sort_by_key(|&(prefix, registration)| (prefix, registration.module_path, registration.type_name));
+
+    let mut collisions: Vec<Collision> = Vec::new();
+    for window in claims.windows(2) {
+        let [(prefix, first), (next_prefix, second)] = window else {
+            unreachable!()
+        };
+        if prefix != next_prefix {
+            continue;
+        }
+        match collisions.last_mut() {
+            Some(collision) if collision.prefix == *prefix => collision.types.push(second),
+            _ => collisions.push(Collision {
+                prefix,
+                types: alloc::vec![*first, *second],
+            }),
+        }
+    }
+    collisions
+}
+
+/// Panics if any prefix is claimed by more than one registered id type.
+///
+/// Meant to be called from a test in the final binary's crate, where all id types are linked in.
+pub fn assert_no_collisions() {
+    let collisions = collisions();
+    if collisions.is_empty() {
+        return;
+    }
+
+    let mut message = alloc::string::String::from("id prefixes claimed by more than one type:");
+    for collision in &collisions {
+        let types: Vec<_> = collision
+            .types
+            .iter()
+            .map(|registration| alloc::format!("{}::{}", registration.module_path, registration.type_name))
+            .collect();
+        message.push_str(&alloc::format!("\n  `{}`: {}", collision.prefix, types.join(", ")));
+    }
+    panic!("{message}");
+}
+
+/// An id of any registered type.
+///
+/// Parsing only succeeds if the prefix belongs to a registered `def_id!` type, which can then be
+/// inspected with [`type_name`](AnyId::type_name) and recovered with [`downcast`](AnyId::downcast).
+#[derive(Clone, Copy)]
+pub struct AnyId {
+    id: Identifier,
+    registration: &'static Registration,
+}
+
+impl AnyId {
+    /// Wraps an id whose prefix is registered.
+    ///
+    /// The id goes through its type's `EntityId::from_identifier`, so ids with a legacy prefix are
+    /// reported to the [`legacy`](crate::legacy) hook.
+    pub fn from_identifier(id: Identifier) -> Result<Self, ParseIdError> {
+        let registration = find(all(), id.prefix())?;
+        Ok(AnyId {
+            id: (registration.from_identifier)(id)?,
+            registration,
+        })
+    }
+
+    /// Parses an id from its bytes, as written by non-human-readable serde formats.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InvalidIdentifierError> {
+        let id = Identifier::from_bytes(bytes)?;
+        AnyId::from_identifier(id).map_err(|_| InvalidIdentifierError)
+    }
+
+    /// The prefix of the id.
+    pub fn prefix(&self) -> &str {
+        self.id.prefix()
+    }
+
+    /// Extracts a string slice containing the entire id.
+    pub fn as_str(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// The bytes of the id.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.id.as_bytes()
+    }
+
+    /// The untyped id.
+    pub fn inner(&self) -> &Identifier {
+        &self.id
+    }
+
+    /// The name of the id's type (e.g. `UserId`).
+    pub fn type_name(&self) -> &'static str {
+        self.registration.type_name
+    }
+
+    /// The registration of the id's type.
+    pub fn registration(&self) -> &'static Registration {
+        self.registration
+    }
+
+    /// Whether the id belongs to the id type `T`.
+    pub fn is<T: 'static>(&self) -> bool {
+        self.registration.is::<T>()
+    }
+
+    /// Whether the id has one of its type's legacy prefixes.
+    pub fn is_legacy(&self) -> bool {
+        self.registration.legacy_prefixes.contains(&self.prefix())
+    }
+
+    /// The id with a legacy prefix replaced by its type's default one, as by the type's own
+    /// `canonicalize`.
+    pub fn canonicalize(self) -> Self {
+        AnyId {
+            id: (self.registration.canonicalize)(self.id),
+            registration: self.registration,
+        }
+    }
+
+    /// Converts the id into its concrete type `T`, or returns `None` if it belongs to another type.
+    pub fn downcast<T: EntityId>(&self) -> Option<T> {
+        if self.is::<T>() {
+            T::from_identifier(self.id).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl PartialEq for AnyId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for AnyId {}
+
+impl core::hash::Hash for AnyId {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl PartialOrd for AnyId {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AnyId {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl core::fmt::Debug for AnyId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}({})", self.type_name(), self.id)
+    }
+}
+
+impl core::fmt::Display for AnyId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.id, f)
+    }
+}
+
+impl FromStr for AnyId {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = Identifier::from_str(s).map_err(|_| ParseIdError::new("AnyId", "a valid id"))?;
+        AnyId::from_identifier(id)
+    }
+}
+
+impl AsRef<str> for AnyId {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<AnyId> for alloc::string::String {
+    fn from(id: AnyId) -> Self {
+        id.id.into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnyId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(self.as_str())
+        } else {
+            serializer.serialize_bytes(self.as_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AnyId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = <alloc::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+            s.parse().map_err(serde::de::Error::custom)
+        } else {
+            let b: &[u8] = serde::Deserialize::deserialize(deserializer)?;
+            AnyId::from_bytes(b).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_registry_impls {
+    ($struct_name:ident, $prefix:literal $(| $alt_prefix:literal)*, legacy [$($legacy_prefix:literal),*]) => {
+        $crate::__private::inventory::submit! {
+            $crate::registry::Registration::new(
+                stringify!($struct_name),
+                module_path!(),
+                &[$prefix $(, $alt_prefix)*],
+                &[$($legacy_prefix),*],
+                ::core::any::TypeId::of::<$struct_name>,
+                |id| <$struct_name as $crate::EntityId>::from_identifier(id).map(|id| id.0),
+                |id| $struct_name(id).canonicalize().0,
+            )
+        }
+    };
+}
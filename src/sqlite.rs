@@ -0,0 +1,123 @@
+//! `rusqlite` support.
+//!
+//! Ids are stored as TEXT by default. Wrapping a `def_id!` struct id in [`Blob`] stores only its
+//! payload, as a 16-byte BLOB. Since the blob does not record the prefix, reading it back restores
+//! the type's default prefix, so only ids with that prefix can be stored this way.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef};
+
+use crate::{EntityId, Identifier, InvalidIdentifierError};
+
+/// Stores an id as the 16-byte BLOB of its payload instead of as TEXT.
+///
+/// Only ids with their type's default prefix and whose payload is the canonical encoding of 128
+/// bits (such as those made by `generate`) can be stored this way.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Blob<T>(pub T);
+
+impl ToSql for Identifier {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(self.as_bytes())))
+    }
+}
+
+impl FromSql for Identifier {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(text) => Identifier::from_bytes(text).map_err(|e| FromSqlError::Other(Box::new(e))),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl<T: EntityId> ToSql for Blob<T> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let id = self.0.inner();
+        if id.prefix() != T::DEFAULT_PREFIX {
+            return Err(rusqlite::Error::ToSqlConversionFailure(
+                format!(
+                    "cannot store `{id}` as a blob, only ids starting with `{}_` can be",
+                    T::DEFAULT_PREFIX,
+                )
+                .into(),
+            ));
+        }
+        let payload = id
+            .payload_bytes()
+            .ok_or_else(|| rusqlite::Error::ToSqlConversionFailure(Box::new(InvalidIdentifierError)))?;
+        Ok(ToSqlOutput::Owned(Value::Blob(payload.to_vec())))
+    }
+}
+
+impl<T: FromSql> FromSql for Blob<T> {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        T::column_result(value).map(Blob)
+    }
+}
+
+/// Reads a typed id from TEXT, or from a 16-byte payload BLOB when the type has a default prefix.
+#[doc(hidden)]
+pub fn column_result<T, E>(
+    value: ValueRef<'_>,
+    default_prefix: Option<&str>,
+    parse: fn(&str) -> Result<T, E>,
+) -> FromSqlResult<T>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    match value {
+        ValueRef::Text(text) => {
+            let s = std::str::from_utf8(text).map_err(|e| FromSqlError::Other(Box::new(e)))?;
+            parse(s).map_err(|e| FromSqlError::Other(Box::new(e)))
+        }
+        ValueRef::Blob(blob) => {
+            let Some(prefix) = default_prefix else {
+                return Err(FromSqlError::InvalidType);
+            };
+            let payload: [u8; 16] = blob.try_into().map_err(|_| FromSqlError::InvalidBlobSize {
+                expected_size: 16,
+                blob_size: blob.len(),
+            })?;
+            let id = Identifier::from_payload(prefix, payload).map_err(|e| FromSqlError::Other(Box::new(e)))?;
+            parse(id.as_str()).map_err(|e| FromSqlError::Other(Box::new(e)))
+        }
+        _ => Err(FromSqlError::InvalidType),
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_rusqlite_impls {
+    ($struct_name:ident) => {
+        impl $crate::__private::rusqlite::types::ToSql for $struct_name {
+            fn to_sql(&self) -> $crate::__private::rusqlite::Result<$crate::__private::rusqlite::types::ToSqlOutput<'_>> {
+                $crate::__private::rusqlite::types::ToSql::to_sql(&self.0)
+            }
+        }
+
+        impl $crate::__private::rusqlite::types::FromSql for $struct_name {
+            fn column_result(
+                value: $crate::__private::rusqlite::types::ValueRef<'_>,
+            ) -> $crate::__private::rusqlite::types::FromSqlResult<Self> {
+                $crate::sqlite::column_result(value, Some(Self::default_prefix()), <Self as ::core::str::FromStr>::from_str)
+            }
+        }
+    };
+    (enum $enum_name:ident) => {
+        impl $crate::__private::rusqlite::types::ToSql for $enum_name {
+            fn to_sql(&self) -> $crate::__private::rusqlite::Result<$crate::__private::rusqlite::types::ToSqlOutput<'_>> {
+                Ok($crate::__private::rusqlite::types::ToSqlOutput::Borrowed(
+                    $crate::__private::rusqlite::types::ValueRef::Text(self.as_bytes()),
+                ))
+            }
+        }
+
+        impl $crate::__private::rusqlite::types::FromSql for $enum_name {
+            fn column_result(
+                value: $crate::__private::rusqlite::types::ValueRef<'_>,
+            ) -> $crate::__private::rusqlite::types::FromSqlResult<Self> {
+                $crate::sqlite::column_result(value, None, <Self as ::core::str::FromStr>::from_str)
+            }
+        }
+    };
+}
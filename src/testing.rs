@@ -0,0 +1,282 @@
+//! Generators for property tests and fuzzing.
+//!
+//! Each of the `arbitrary`, `proptest` and `quickcheck` features implements that crate's
+//! `Arbitrary` for [`Identifier`] and `def_id!` types. Typed ids only use their own prefixes, and
+//! `def_id!(enum ...)` unions pick a variant first. Every submodule also produces near misses:
+//! strings one mutation away from a valid id, which must be rejected by the parser. The typed
+//! `near_miss_for::<T>` functions also avoid `T`'s legacy prefixes, which parse without being
+//! listed in `T::prefixes()`.
+
+use crate::Identifier;
+
+const ALPHANUMERIC: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The number of distinct mutations `near_miss` can apply.
+const NEAR_MISSES: usize = 7;
+
+/// Builds a prefix from indexes into the alphanumeric characters.
+fn prefix_from_indexes(indexes: impl IntoIterator<Item = usize>) -> String {
+    indexes
+        .into_iter()
+        .map(|i| ALPHANUMERIC[i % ALPHANUMERIC.len()] as char)
+        .collect()
+}
+
+fn id_from_parts(prefix: &str, payload: [u8; 16]) -> Identifier {
+    Identifier::from_payload(prefix, payload).expect("generated prefixes are valid")
+}
+
+/// Applies one of the near miss mutations to `valid`, an id starting with one of `prefixes`.
+///
+/// With no `prefixes`, the result is invalid as any id, otherwise it is invalid for a type
+/// accepting exactly `prefixes` and `legacy`.
+pub(crate) fn near_miss(valid: &Identifier, prefixes: &[&str], legacy: &[&str], mutation: usize, filler: usize) -> String {
+    let (prefix, payload) = (valid.prefix(), valid.payload());
+    match mutation % NEAR_MISSES {
+        // Missing separator
+        0 => format!("{prefix}{payload}"),
+        // Empty payload
+        1 => format!("{prefix}_"),
+        // Payload one character too long
+        2 => format!("{valid}{}", "0".repeat(23 - payload.len())),
+        // Prefix one character too long
+        3 => format!("{}_{payload}", prefix.repeat(9).get(..9).unwrap()),
+        // A character outside of `[a-zA-Z0-9]` in the payload
+        4 => {
+            let bad = ['-', '.', ' ', 'é'][filler % 4];
+            let i = filler % payload.len();
+            format!("{prefix}_{}{bad}{}", &payload[..i], &payload[i + 1..])
+        }
+        // A prefix that is not accepted
+        5 if prefixes.is_empty() => format!("_{payload}"),
+        5 => {
+            let other = (0..ALPHANUMERIC.len())
+                .map(|i| ALPHANUMERIC[filler.wrapping_add(i) % ALPHANUMERIC.len()] as char)
+                .map(|last| format!("{}{last}", &prefix[..prefix.len() - 1]))
+                .find(|candidate| !prefixes.contains(&candidate.as_str()) && !legacy.contains(&candidate.as_str()))
+                .unwrap();
+            format!("{other}_{payload}")
+        }
+        // Surrounding whitespace
+        _ => format!(" {valid}"),
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary {
+    use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+    use crate::{EntityId, Identifier};
+
+    impl<'a> Arbitrary<'a> for Identifier {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            id(u, &[])
+        }
+    }
+
+    /// A valid id starting with one of `prefixes`, or with any prefix if `prefixes` is empty.
+    pub fn id(u: &mut Unstructured<'_>, prefixes: &[&str]) -> Result<Identifier> {
+        let prefix = if prefixes.is_empty() {
+            let len = u.int_in_range(1..=8)?;
+            let indexes = (0..len).map(|_| u.choose_index(62)).collect::<Result<Vec<_>>>()?;
+            super::prefix_from_indexes(indexes)
+        } else {
+            u.choose(prefixes)?.to_string()
+        };
+        Ok(super::id_from_parts(&prefix, u.arbitrary()?))
+    }
+
+    /// A string one mutation away from an id starting with one of `prefixes`.
+    ///
+    /// Include the type's legacy prefixes in `prefixes`, or use [`near_miss_for`].
+    pub fn near_miss(u: &mut Unstructured<'_>, prefixes: &[&str]) -> Result<String> {
+        let valid = id(u, prefixes)?;
+        Ok(super::near_miss(&valid, prefixes, &[], u.arbitrary()?, u.arbitrary()?))
+    }
+
+    /// A string one mutation away from an id of type `T`, which `T` rejects.
+    pub fn near_miss_for<T: EntityId>(u: &mut Unstructured<'_>) -> Result<String> {
+        let valid = id(u, T::PREFIXES)?;
+        Ok(super::near_miss(&valid, T::PREFIXES, T::LEGACY_PREFIXES, u.arbitrary()?, u.arbitrary()?))
+    }
+
+    #[doc(hidden)]
+    #[macro_export]
+    macro_rules! def_id_arbitrary_impls {
+        ($struct_name:ident) => {
+            impl<'a> $crate::__private::arbitrary::Arbitrary<'a> for $struct_name {
+                fn arbitrary(
+                    u: &mut $crate::__private::arbitrary::Unstructured<'a>,
+                ) -> $crate::__private::arbitrary::Result<Self> {
+                    $crate::testing::arbitrary::id(u, Self::prefixes()).map(Self)
+                }
+            }
+        };
+        (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+            impl<'a> $crate::__private::arbitrary::Arbitrary<'a> for $enum_name {
+                fn arbitrary(
+                    u: &mut $crate::__private::arbitrary::Unstructured<'a>,
+                ) -> $crate::__private::arbitrary::Result<Self> {
+                    let variants: &[fn(
+                        &mut $crate::__private::arbitrary::Unstructured<'a>,
+                    ) -> $crate::__private::arbitrary::Result<$enum_name>] = &[
+                        $( |u| Ok($enum_name::$variant_name(u.arbitrary()?)), )*
+                    ];
+                    u.choose(variants)?(u)
+                }
+            }
+        };
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub mod proptest {
+    use ::proptest::arbitrary::{any, Arbitrary};
+    use ::proptest::collection::vec;
+    use ::proptest::sample::select;
+    use ::proptest::strategy::{BoxedStrategy, Strategy};
+
+    use crate::{EntityId, Identifier};
+
+    impl Arbitrary for Identifier {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            ids(&[])
+        }
+    }
+
+    /// Valid ids starting with one of `prefixes`, or with any prefix if `prefixes` is empty.
+    pub fn ids(prefixes: &[&'static str]) -> BoxedStrategy<Identifier> {
+        let prefix = if prefixes.is_empty() {
+            vec(0..62usize, 1..=8).prop_map(super::prefix_from_indexes).boxed()
+        } else {
+            select(prefixes.to_vec()).prop_map(str::to_string).boxed()
+        };
+        (prefix, any::<[u8; 16]>())
+            .prop_map(|(prefix, payload)| super::id_from_parts(&prefix, payload))
+            .boxed()
+    }
+
+    /// Strings one mutation away from an id starting with one of `prefixes`.
+    ///
+    /// Include the type's legacy prefixes in `prefixes`, or use [`near_misses_for`].
+    pub fn near_misses(prefixes: &[&'static str]) -> BoxedStrategy<String> {
+        let prefixes = prefixes.to_vec();
+        (ids(&prefixes), any::<usize>(), any::<usize>())
+            .prop_map(move |(valid, mutation, filler)| super::near_miss(&valid, &prefixes, &[], mutation, filler))
+            .boxed()
+    }
+
+    /// Strings one mutation away from an id of type `T`, which `T` rejects.
+    pub fn near_misses_for<T: EntityId>() -> BoxedStrategy<String> {
+        (ids(T::PREFIXES), any::<usize>(), any::<usize>())
+            .prop_map(|(valid, mutation, filler)| {
+                super::near_miss(&valid, T::PREFIXES, T::LEGACY_PREFIXES, mutation, filler)
+            })
+            .boxed()
+    }
+
+    #[doc(hidden)]
+    #[macro_export]
+    macro_rules! def_id_proptest_impls {
+        ($struct_name:ident) => {
+            impl $crate::__private::proptest::arbitrary::Arbitrary for $struct_name {
+                type Parameters = ();
+                type Strategy = $crate::__private::proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: ()) -> Self::Strategy {
+                    $crate::__private::proptest::strategy::Strategy::boxed(
+                        $crate::__private::proptest::strategy::Strategy::prop_map(
+                            $crate::testing::proptest::ids(Self::prefixes()),
+                            Self,
+                        ),
+                    )
+                }
+            }
+        };
+        (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+            impl $crate::__private::proptest::arbitrary::Arbitrary for $enum_name {
+                type Parameters = ();
+                type Strategy = $crate::__private::proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: ()) -> Self::Strategy {
+                    $crate::__private::proptest::strategy::Strategy::boxed(
+                        $crate::__private::proptest::strategy::Union::new([
+                            $(
+                                $crate::__private::proptest::strategy::Strategy::boxed(
+                                    $crate::__private::proptest::strategy::Strategy::prop_map(
+                                        $crate::__private::proptest::arbitrary::any::<$($variant_type)*>(),
+                                        $enum_name::$variant_name,
+                                    ),
+                                ),
+                            )*
+                        ]),
+                    )
+                }
+            }
+        };
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+pub mod quickcheck {
+    use ::quickcheck::{Arbitrary, Gen};
+
+    use crate::{EntityId, Identifier};
+
+    impl Arbitrary for Identifier {
+        fn arbitrary(g: &mut Gen) -> Self {
+            id(g, &[])
+        }
+    }
+
+    /// A valid id starting with one of `prefixes`, or with any prefix if `prefixes` is empty.
+    pub fn id(g: &mut Gen, prefixes: &[&str]) -> Identifier {
+        let prefix = match g.choose(prefixes) {
+            Some(prefix) => prefix.to_string(),
+            None => {
+                let len = usize::arbitrary(g) % 8 + 1;
+                super::prefix_from_indexes((0..len).map(|_| usize::arbitrary(g)))
+            }
+        };
+        super::id_from_parts(&prefix, u128::arbitrary(g).to_be_bytes())
+    }
+
+    /// A string one mutation away from an id starting with one of `prefixes`.
+    ///
+    /// Include the type's legacy prefixes in `prefixes`, or use [`near_miss_for`].
+    pub fn near_miss(g: &mut Gen, prefixes: &[&str]) -> String {
+        let valid = id(g, prefixes);
+        super::near_miss(&valid, prefixes, &[], usize::arbitrary(g), usize::arbitrary(g))
+    }
+
+    /// A string one mutation away from an id of type `T`, which `T` rejects.
+    pub fn near_miss_for<T: EntityId>(g: &mut Gen) -> String {
+        let valid = id(g, T::PREFIXES);
+        super::near_miss(&valid, T::PREFIXES, T::LEGACY_PREFIXES, usize::arbitrary(g), usize::arbitrary(g))
+    }
+
+    #[doc(hidden)]
+    #[macro_export]
+    macro_rules! def_id_quickcheck_impls {
+        ($struct_name:ident) => {
+            impl $crate::__private::quickcheck::Arbitrary for $struct_name {
+                fn arbitrary(g: &mut $crate::__private::quickcheck::Gen) -> Self {
+                    Self($crate::testing::quickcheck::id(g, Self::prefixes()))
+                }
+            }
+        };
+        (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+            impl $crate::__private::quickcheck::Arbitrary for $enum_name {
+                fn arbitrary(g: &mut $crate::__private::quickcheck::Gen) -> Self {
+                    let variants: &[fn(&mut $crate::__private::quickcheck::Gen) -> $enum_name] = &[
+                        $( |g| $enum_name::$variant_name($crate::__private::quickcheck::Arbitrary::arbitrary(g)), )*
+                    ];
+                    g.choose(variants).unwrap()(g)
+                }
+            }
+        };
+    }
+}
@@ -18,22 +18,119 @@ def_id!(
         User(UserId),
     }
 );
+def_id!(CardId, "card");
+def_id!(BankAccountId, "ba");
+def_id!(WalletId, "wal");
+def_id!(CustomerId, "cus", legacy: "cust" | "cu");
+def_id!(
+    enum PaymentSource {
+        Card(CardId),
+        BankAccount(BankAccountId),
+        Wallet(WalletId),
+    }
+);
+def_id!(
+    enum Reference {
+        Source(PaymentSource),
+        Charge(ChargeId),
+    }
+);
+
+#[cfg(feature = "derive")]
+mod derived {
+    use crate::{EntityId, Identifier};
+
+    /// A payment, either a card payment or a bank transfer.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EntityId)]
+    #[entity_id(prefix = "pay", alt = ["tr"])]
+    pub struct PaymentId(Identifier);
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EntityId)]
+    #[entity_id(prefix = "sess", legacy = "session", vis = "pub(crate)")]
+    pub struct SessionId(crate::Identifier);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive() {
+    use derived::{PaymentId, SessionId};
+
+    let payment_id = PaymentId::generate();
+    assert_eq!(payment_id.prefix(), "pay");
+    assert_eq!(PaymentId::prefixes(), ["pay", "tr"]);
+    assert_eq!(payment_id, payment_id.to_string().parse::<PaymentId>().unwrap());
+    assert!("tr_C3M2XCLwa3LjkkH4V15muQ".parse::<PaymentId>().is_ok());
+    assert!("user_C3M2XCLwa3LjkkH4V15muQ".parse::<PaymentId>().is_err());
+    assert_eq!(format!("{:?}", PaymentId::from_bytes(b"tr_0").unwrap()), "PaymentId(tr_0)");
+
+    let session_id = SessionId::generate();
+    assert_eq!(session_id.prefix(), "sess");
+    let legacy = "session_C3M2XCLwa3LjkkH4V15muQ".parse::<SessionId>().unwrap();
+    assert!(legacy.is_legacy());
+    assert_eq!(legacy.canonicalize().as_str(), "sess_C3M2XCLwa3LjkkH4V15muQ");
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_registry() {
+    use crate::registry::{self, Registration};
+    use std::any::TypeId;
+
+    let charge = registry::lookup("py").unwrap();
+    assert_eq!(charge.type_name(), "ChargeId");
+    assert_eq!(charge.module_path(), module_path!());
+    assert_eq!(charge.prefixes(), ["ch", "py"]);
+    assert_eq!(registry::lookup("user").unwrap().type_name(), "UserId");
+    assert!(registry::lookup("nope").is_none());
+    let customer = registry::lookup("cust").unwrap();
+    assert_eq!(customer.type_name(), "CustomerId");
+    assert_eq!(customer.prefixes(), ["cus"]);
+    assert_eq!(customer.legacy_prefixes(), ["cust", "cu"]);
+    assert!(registry::all().any(|r| r.type_name() == "AccountId"));
+    // Every id type in the test binary needs its own prefixes for this to pass
+    registry::assert_no_collisions();
+
+    static CUSTOMER: Registration = Registration::new("CustomerId", "billing", &["cus", "ch"], &[], TypeId::of::<()>, Ok, |id| id);
+    static CHARGE: Registration = Registration::new("ChargeId", "payments", &["ch", "py"], &[], TypeId::of::<()>, Ok, |id| id);
+    static LEGACY: Registration = Registration::new("LegacyChargeId", "legacy", &["chg"], &["ch"], TypeId::of::<()>, Ok, |id| id);
+    let collisions = registry::find_collisions([&CUSTOMER, &CHARGE, &LEGACY].into_iter());
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].prefix, "ch");
+    let types: Vec<_> = collisions[0].types.iter().map(|r| r.type_name()).collect();
+    assert_eq!(types, ["CustomerId", "LegacyChargeId", "ChargeId"]);
+
+    // An ambiguous prefix resolves to no type rather than the first one linked
+    let fixture = || [&CUSTOMER, &CHARGE, &LEGACY].into_iter();
+    assert_eq!(registry::find(fixture(), "py").unwrap().type_name(), "ChargeId");
+    assert_eq!(registry::find(fixture(), "chg").unwrap().type_name(), "LegacyChargeId");
+    let err = registry::find(fixture(), "ch").unwrap_err();
+    assert_eq!(err.expected(), "an id whose prefix is registered by a single type");
+    assert!(registry::find(fixture(), "nope").is_err());
+}
+
+#[test]
+fn test_compile_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/def_id/*.rs");
+    #[cfg(feature = "derive")]
+    t.compile_fail("tests/ui/derive/*.rs");
+}
 
 #[test]
 fn test_prefix() {
-    let account_id = AccountId::generate().unwrap();
+    let account_id = AccountId::generate();
     assert_eq!(account_id.prefix(), "acct");
     assert_eq!(AccountId::prefixes(), ["acct"]);
     assert!(AccountId::is_valid_prefix("acct"));
     assert!(!AccountId::is_valid_prefix("acct_"));
 
-    let user_id = UserId::generate().unwrap();
+    let user_id = UserId::generate();
     assert_eq!(user_id.prefix(), "user");
     assert_eq!(UserId::prefixes(), ["user"]);
     assert!(UserId::is_valid_prefix("user"));
     assert!(!UserId::is_valid_prefix("user_"));
 
-    let charge_id = ChargeId::generate().unwrap();
+    let charge_id = ChargeId::generate();
     assert_eq!(charge_id.prefix(), "ch");
     assert_eq!(ChargeId::prefixes(), ["ch", "py"]);
     assert!(ChargeId::is_valid_prefix("ch"));
@@ -64,15 +161,914 @@ fn test_enum() {
     assert!(bad_enum.is_err())
 }
 
+#[test]
+fn test_enum_from_str() {
+    fn round_trip<T>(id: T)
+    where
+        T: FromStr<Err = ParseIdError> + Debug + PartialEq + std::fmt::Display,
+    {
+        let s = id.to_string();
+        assert_eq!(s.parse::<T>().unwrap(), id, "{s}");
+    }
+
+    round_trip(UserOrAccount::Account(AccountId::generate()));
+    round_trip(UserOrAccount::User(UserId::generate()));
+    for prefix in UserOrCharge::PREFIXES {
+        round_trip(UserOrCharge::generate_with_prefix(prefix).unwrap());
+    }
+    for prefix in Reference::PREFIXES {
+        round_trip(Reference::generate_with_prefix(prefix).unwrap());
+    }
+
+    let parsed: UserOrCharge = "py_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap();
+    assert_eq!(parsed, UserOrCharge::Charge("py_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap()));
+    assert_eq!(parsed, UserOrCharge::from_bytes(b"py_C3M2XCLwa3LjkkH4V15muQ").unwrap());
+
+    let err = "acct_C3M2XCLwa3LjkkH4V15muQ".parse::<UserOrCharge>().unwrap_err();
+    assert_eq!(err.typename(), "UserOrCharge");
+    assert_eq!(err.prefixes(), ["ch", "py", "user"]);
+    assert_eq!(err.to_string(), "invalid `UserOrCharge`, expected an id starting with `ch_`, `py_` or `user_`");
+
+    let err = "ch_".parse::<UserOrCharge>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid `UserOrCharge`, expected a valid id starting with `ch_`, `py_` or `user_`");
+
+    let err = "user_C3M2XCLwa3LjkkH4V15muQ".parse::<ChargeId>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid `ChargeId`, expected an id starting with `ch_` or `py_`");
+    let err = "nope".parse::<UserId>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid `UserId`, expected a valid id starting with `user_`");
+}
+
+#[test]
+fn test_nested_enum() {
+    let card_id = CardId::generate();
+    let source: PaymentSource = card_id.into();
+    let reference: Reference = source.into();
+    let copy = reference;
+    assert_eq!(copy, reference);
+
+    assert_eq!(reference.as_str(), card_id.as_str());
+    assert_eq!(reference.prefix(), "card");
+    assert_eq!(reference.variant_name(), "Source");
+    assert_eq!(source.variant_name(), "Card");
+    assert_eq!(reference.inner(), card_id.inner());
+
+    assert_eq!(Reference::PREFIXES, ["card", "ba", "wal", "ch", "py"]);
+    assert!(Reference::is_valid_prefix("wal"));
+    assert!(!Reference::is_valid_prefix("user"));
+    assert_eq!(
+        Reference::variant_type_names().collect::<Vec<_>>(),
+        ["PaymentSource", "ChargeId"],
+    );
+    assert_eq!(
+        PaymentSource::variant_type_names().collect::<Vec<_>>(),
+        ["CardId", "BankAccountId", "WalletId"],
+    );
+
+    let parsed = Reference::from_bytes(b"ba_C3M2XCLwa3LjkkH4V15muQ").unwrap();
+    assert_eq!(
+        parsed,
+        Reference::Source(PaymentSource::BankAccount(BankAccountId::from_bytes(b"ba_C3M2XCLwa3LjkkH4V15muQ").unwrap())),
+    );
+    assert!(matches!(Reference::from_bytes(b"py_0").unwrap(), Reference::Charge(_)));
+    assert!(Reference::from_bytes(b"user_0").is_err());
+
+    let wallet = Reference::generate_with_prefix("wal").unwrap();
+    assert!(matches!(wallet, Reference::Source(PaymentSource::Wallet(_))));
+}
+
+#[test]
+fn test_enum_prefixes() {
+    assert_eq!(UserOrCharge::PREFIXES, ["ch", "py", "user"]);
+    assert_eq!(UserOrCharge::prefixes(), UserOrCharge::PREFIXES);
+    assert_eq!(UserOrAccount::prefixes(), ["acct", "user"]);
+    assert_eq!(ChargeId::PREFIXES, ["ch", "py"]);
+
+    assert!(UserOrCharge::is_valid_prefix("py"));
+    assert!(UserOrCharge::is_valid_prefix_bytes(b"user"));
+    assert!(!UserOrCharge::is_valid_prefix("acct"));
+
+    let charge: UserOrCharge = ChargeId::generate().into();
+    assert_eq!(charge.prefix(), "ch");
+
+    let payment = UserOrCharge::generate_with_prefix("py").unwrap();
+    assert!(matches!(payment, UserOrCharge::Charge(_)));
+    assert_eq!(payment.prefix(), "py");
+    let user = UserOrCharge::generate_with_prefix("user").unwrap();
+    assert!(matches!(user, UserOrCharge::User(_)));
+    assert!(UserOrCharge::generate_with_prefix("acct").is_none());
+
+    let generated = UserOrCharge::generate();
+    assert!(matches!(generated, UserOrCharge::Charge(_)));
+    assert_eq!(generated.prefix(), "ch");
+    let generated = Reference::generate();
+    assert!(matches!(generated, Reference::Source(PaymentSource::Card(_))));
+    assert_eq!(generated.prefix(), "card");
+    assert_ne!(Reference::generate(), generated);
+
+    assert_eq!(ChargeId::generate_with_prefix("py").unwrap().prefix(), "py");
+    assert!(ChargeId::generate_with_prefix("user").is_none());
+}
+
 #[test]
 fn test_round_trips() {
-    let account_id = AccountId::generate().unwrap();
+    let account_id = AccountId::generate();
     let account_id_str = account_id.to_string();
     let account_id2 = AccountId::from_str(&account_id_str).unwrap();
     assert_eq!(account_id, account_id2);
 
-    let user_id = UserId::generate().unwrap();
+    let user_id = UserId::generate();
     let user_id_str = user_id.as_str();
-    let user_id2 = UserId::from_str(&user_id_str).unwrap();
+    let user_id2 = UserId::from_str(user_id_str).unwrap();
     assert_eq!(user_id, user_id2);
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "rusqlite")]
+#[test]
+fn test_rusqlite() {
+    use crate::sqlite::Blob;
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE ids (id, account_blob BLOB)").unwrap();
+
+    let account_id = AccountId::generate();
+    conn.execute("INSERT INTO ids VALUES (?1, ?2)", (account_id, Blob(account_id))).unwrap();
+
+    let (text, blob): (AccountId, Blob<AccountId>) =
+        conn.query_row("SELECT id, account_blob FROM ids", [], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+    assert_eq!(text, account_id);
+    assert_eq!(blob.0, account_id);
+
+    let stored: Vec<u8> = conn.query_row("SELECT account_blob FROM ids", [], |row| row.get(0)).unwrap();
+    assert_eq!(stored.len(), 16);
+
+    let untyped: Identifier = conn.query_row("SELECT id FROM ids", [], |row| row.get(0)).unwrap();
+    assert_eq!(untyped, account_id.as_str());
+
+    let either: UserOrAccount = conn.query_row("SELECT id FROM ids", [], |row| row.get(0)).unwrap();
+    assert_eq!(either, account_id);
+
+    // A row whose prefix belongs to another id type is rejected
+    let err = conn.query_row("SELECT id FROM ids", [], |row| row.get::<_, UserId>(0)).unwrap_err();
+    assert!(matches!(err, rusqlite::Error::FromSqlConversionFailure(..)));
+    assert!(err.to_string().contains("UserId"));
+    let err = conn.query_row("SELECT id FROM ids", [], |row| row.get::<_, UserOrCharge>(0)).unwrap_err();
+    assert!(err.to_string().contains("invalid `UserOrCharge`, expected an id starting with `ch_`, `py_` or `user_`"));
+
+    // Blobs only hold the payload, so only ids with the default prefix can be stored
+    let charge_id: ChargeId = "py_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap();
+    let err = conn.execute("INSERT INTO ids VALUES (?1, ?2)", (charge_id, Blob(charge_id))).unwrap_err();
+    assert!(matches!(err, rusqlite::Error::ToSqlConversionFailure(..)));
+    assert_eq!(
+        err.to_string(),
+        "cannot store `py_C3M2XCLwa3LjkkH4V15muQ` as a blob, only ids starting with `ch_` can be",
+    );
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn test_postgres() {
+    use bytes::BytesMut;
+    use postgres_types::{FromSql, ToSql, Type};
+
+    let charge_id: ChargeId = "py_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap();
+
+    let mut buf = BytesMut::new();
+    charge_id.to_sql_checked(&Type::TEXT, &mut buf).unwrap();
+    assert_eq!(&buf[..], charge_id.as_bytes());
+    assert_eq!(ChargeId::from_sql(&Type::TEXT, &buf).unwrap(), charge_id);
+    assert_eq!(Identifier::from_sql(&Type::VARCHAR, &buf).unwrap(), charge_id.as_str());
+
+    // Reads validate the prefix against the column's id type
+    let err = UserId::from_sql(&Type::TEXT, &buf).unwrap_err();
+    assert!(err.to_string().contains("UserId"));
+
+    // uuid columns only hold the payload, so only ids with the default prefix can be stored
+    let err = charge_id.to_sql_checked(&Type::UUID, &mut BytesMut::new()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "cannot store `py_C3M2XCLwa3LjkkH4V15muQ` in a uuid column, only ids starting with `ch_` can be",
+    );
+    let charge_id = ChargeId::generate();
+    let mut buf = BytesMut::new();
+    charge_id.to_sql_checked(&Type::UUID, &mut buf).unwrap();
+    assert_eq!(buf.len(), 16);
+    assert_eq!(ChargeId::from_sql(&Type::UUID, &buf).unwrap(), charge_id);
+
+    let err = UserOrCharge::from_sql(&Type::TEXT, b"acct_0").unwrap_err();
+    assert_eq!(err.to_string(), "invalid `UserOrCharge`, expected an id starting with `ch_`, `py_` or `user_`");
+
+    assert!(<Identifier as ToSql>::accepts(&Type::TEXT));
+    assert!(!<Identifier as FromSql>::accepts(&Type::UUID));
+    assert!(!<UserOrCharge as FromSql>::accepts(&Type::UUID));
+    assert!(!<UserId as FromSql>::accepts(&Type::INT8));
+    let err = charge_id.to_sql_checked(&Type::INT8, &mut BytesMut::new()).err().unwrap();
+    assert!(err.is::<postgres_types::WrongType>());
+    assert!(UserOrCharge::from(charge_id).to_sql_checked(&Type::UUID, &mut BytesMut::new()).is_err());
+}
+
+#[test]
+fn test_try_from_string() {
+    let user_id = UserId::try_from("user_C3M2XCLwa3LjkkH4V15muQ".to_string()).unwrap();
+    assert_eq!(user_id, "user_C3M2XCLwa3LjkkH4V15muQ");
+
+    let err = AccountId::try_from("user_C3M2XCLwa3LjkkH4V15muQ").unwrap_err();
+    assert!(err.to_string().contains("AccountId"));
+}
+
+#[cfg(feature = "prost")]
+#[test]
+fn test_prost() {
+    use prost::Message;
+
+    let user_id = UserId::generate();
+    let message = crate::proto::Identifier::from(user_id);
+    let decoded = crate::proto::Identifier::decode(message.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(UserId::try_from(decoded.clone()).unwrap(), user_id);
+    assert_eq!(Identifier::try_from(decoded.clone()).unwrap(), user_id.as_str());
+    assert!(AccountId::try_from(decoded).is_err());
+
+    let either: UserOrAccount = user_id.into();
+    assert_eq!(crate::proto::Identifier::from(either).value, user_id.as_str());
+    assert_eq!(<crate::proto::Identifier as prost::Name>::full_name(), "entity_ident.Identifier");
+}
+
+#[cfg(feature = "tonic")]
+#[test]
+fn test_tonic_status() {
+    let status: tonic::Status = UserId::try_from("acct_C3M2XCLwa3LjkkH4V15muQ").unwrap_err().into();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert!(status.message().contains("UserId"));
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn test_json_schema() {
+    let schema = schemars::schema_for!(ChargeId);
+    assert_eq!(schema.get("type").unwrap(), "string");
+    assert_eq!(schema.get("pattern").unwrap(), "^(ch|py)_[a-zA-Z0-9]{1,22}$");
+    assert_eq!(schema.get("maxLength").unwrap(), 31);
+    assert_eq!(schema.get("examples").unwrap()[0], "ch_C3M2XCLwa3LjkkH4V15muQ");
+    assert_eq!(schemars::schema_for!(ChargeId), schema);
+
+    let schema = schemars::schema_for!(UserOrAccount);
+    let variants = schema.get("oneOf").unwrap().as_array().unwrap();
+    assert_eq!(variants[0]["$ref"], "#/$defs/AccountId");
+    assert_eq!(schema.get("$defs").unwrap()["UserId"]["pattern"], "^(user)_[a-zA-Z0-9]{1,22}$");
+}
+
+#[cfg(feature = "utoipa")]
+#[test]
+fn test_utoipa() {
+    use utoipa::openapi::schema::Schema;
+    use utoipa::openapi::RefOr;
+    use utoipa::{IntoParams, PartialSchema, ToSchema};
+
+    let RefOr::T(Schema::Object(schema)) = ChargeId::schema() else {
+        panic!("expected an inline object schema");
+    };
+    assert_eq!(schema.pattern.as_deref(), Some("^(ch|py)_[a-zA-Z0-9]{1,22}$"));
+    assert_eq!(schema.max_length, Some(31));
+    assert_eq!(schema.examples, ["ch_C3M2XCLwa3LjkkH4V15muQ"]);
+
+    let RefOr::T(Schema::OneOf(one_of)) = UserOrAccount::schema() else {
+        panic!("expected a oneOf schema");
+    };
+    assert_eq!(one_of.items.len(), 2);
+
+    let mut schemas = Vec::new();
+    UserOrAccount::schemas(&mut schemas);
+    let names: Vec<_> = schemas.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["AccountId", "UserId"]);
+
+    #[derive(utoipa::ToSchema)]
+    #[allow(dead_code)]
+    struct Transfer {
+        from: UserOrAccount,
+        charge: ChargeId,
+    }
+    let mut schemas = Vec::new();
+    Transfer::schemas(&mut schemas);
+    assert!(schemas.iter().any(|(name, _)| name == "ChargeId"));
+
+    let params = UserId::into_params(|| None);
+    assert_eq!(params.len(), 1);
+    assert_eq!(params[0].name, "user_id");
+}
+
+#[cfg(feature = "async-graphql")]
+#[test]
+fn test_async_graphql() {
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn charge(&self, id: ChargeId) -> ChargeId {
+            id
+        }
+
+        async fn owner(&self, id: UserId) -> UserOrAccount {
+            id.into()
+        }
+    }
+
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    let sdl = schema.sdl();
+    assert!(sdl.contains("scalar ChargeId"));
+    assert!(sdl.contains("A `ChargeId`, starting with `ch_` or `py_`."));
+    assert!(sdl.contains("A `UserOrAccount`, which is one of `AccountId` or `UserId`, starting with `acct_` or `user_`."));
+
+    let response = futures::executor::block_on(schema.execute(r#"{ charge(id: "py_C3M2XCLwa3LjkkH4V15muQ") }"#));
+    assert!(response.errors.is_empty());
+    assert_eq!(response.data.to_string(), r#"{charge: "py_C3M2XCLwa3LjkkH4V15muQ"}"#);
+
+    let response = futures::executor::block_on(schema.execute(r#"{ charge(id: "user_C3M2XCLwa3LjkkH4V15muQ") }"#));
+    assert!(response.errors[0].message.contains("ChargeId"));
+
+    let response = futures::executor::block_on(schema.execute(r#"{ owner(id: "user_C3M2XCLwa3LjkkH4V15muQ") }"#));
+    assert!(response.errors.is_empty());
+    assert_eq!(response.data.to_string(), r#"{owner: "user_C3M2XCLwa3LjkkH4V15muQ"}"#);
+
+    let response = futures::executor::block_on(schema.execute(r#"{ charge(id: 1) }"#));
+    assert_eq!(response.errors.len(), 1);
+}
+
+#[cfg(feature = "axum")]
+#[test]
+fn test_axum_path() {
+    use ::axum::body::Body;
+    use ::axum::http::{Request, StatusCode};
+    use ::axum::routing::get;
+    use ::axum::Router;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    async fn get_user(crate::axum::Path(user_id): crate::axum::Path<UserId>) -> String {
+        user_id.to_string()
+    }
+
+    async fn get_charge(
+        crate::axum::Path(user_id): crate::axum::Path<UserId>,
+        crate::axum::Path(charge_id): crate::axum::Path<ChargeId>,
+    ) -> String {
+        format!("{user_id} {charge_id}")
+    }
+
+    async fn list_charges(crate::axum::Query(user_id): crate::axum::Query<UserId>) -> String {
+        user_id.to_string()
+    }
+
+    let app = Router::new()
+        .route("/users/{user_id}", get(get_user))
+        .route("/accounts/{id}", get(get_user))
+        .route("/accounts/{account_id}/owner", get(get_user))
+        .route("/users/{user_id}/charges/{charge_id}", get(get_charge))
+        .route("/charges", get(list_charges));
+    let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let read_body = |response: ::axum::response::Response| async {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    };
+
+    futures::executor::block_on(async {
+        let response = app.clone().oneshot(request("/users/user_C3M2XCLwa3LjkkH4V15muQ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(request("/users/acct_C3M2XCLwa3LjkkH4V15muQ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["type"], "UserId");
+        assert_eq!(body["error"], "invalid_id");
+
+        // A single parameter can be called `id`, but not after another type
+        let response = app.clone().oneshot(request("/accounts/user_C3M2XCLwa3LjkkH4V15muQ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(request("/accounts/user_C3M2XCLwa3LjkkH4V15muQ/owner")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(read_body(response).await, "route has no `user_id` path parameter for `UserId`");
+
+        let response = app
+            .clone()
+            .oneshot(request("/users/user_C3M2XCLwa3LjkkH4V15muQ/charges/ch_C3M2XCLwa3LjkkH4V15muQ"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(read_body(response).await, "user_C3M2XCLwa3LjkkH4V15muQ ch_C3M2XCLwa3LjkkH4V15muQ");
+
+        let response = app.clone().oneshot(request("/charges?user_id=user_C3M2XCLwa3LjkkH4V15muQ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(read_body(response).await, "user_C3M2XCLwa3LjkkH4V15muQ");
+
+        let response = app.clone().oneshot(request("/charges?user_id=ch_C3M2XCLwa3LjkkH4V15muQ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.oneshot(request("/charges")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_str(&read_body(response).await).unwrap();
+        assert_eq!(body["type"], "UserId");
+    });
+}
+
+#[cfg(feature = "actix-web")]
+#[test]
+fn test_actix_path() {
+    use actix_web::FromRequest;
+
+    let req = actix_web::test::TestRequest::default()
+        .param("user_id", "user_C3M2XCLwa3LjkkH4V15muQ")
+        .to_http_request();
+    let user_id = crate::actix::Path::<UserId>::extract(&req).into_inner().unwrap();
+    assert_eq!(*user_id, "user_C3M2XCLwa3LjkkH4V15muQ");
+
+    // A single parameter can be called `id`, but not after another type
+    let req = actix_web::test::TestRequest::default()
+        .param("id", "user_C3M2XCLwa3LjkkH4V15muQ")
+        .to_http_request();
+    let user_id = crate::actix::Path::<UserId>::extract(&req).into_inner().unwrap();
+    assert_eq!(*user_id, "user_C3M2XCLwa3LjkkH4V15muQ");
+    let req = actix_web::test::TestRequest::default()
+        .param("account_id", "user_C3M2XCLwa3LjkkH4V15muQ")
+        .to_http_request();
+    let err = crate::actix::Path::<UserId>::extract(&req).into_inner().unwrap_err();
+    let response = err.as_response_error().error_response();
+    assert_eq!(response.status(), actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+
+    let req = actix_web::test::TestRequest::default()
+        .param("user_id", "acct_C3M2XCLwa3LjkkH4V15muQ")
+        .to_http_request();
+    let err = crate::actix::Path::<UserId>::extract(&req).into_inner().unwrap_err();
+    let response = err.as_response_error().error_response();
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let req = actix_web::test::TestRequest::default()
+        .param("user_id", "user_C3M2XCLwa3LjkkH4V15muQ")
+        .param("charge_id", "ch_C3M2XCLwa3LjkkH4V15muQ")
+        .to_http_request();
+    let charge_id = crate::actix::Path::<ChargeId>::extract(&req).into_inner().unwrap();
+    assert_eq!(*charge_id, "ch_C3M2XCLwa3LjkkH4V15muQ");
+    let user_id = crate::actix::Path::<UserId>::extract(&req).into_inner().unwrap();
+    assert_eq!(*user_id, "user_C3M2XCLwa3LjkkH4V15muQ");
+    let err = crate::actix::Path::<AccountId>::extract(&req).into_inner().unwrap_err();
+    let response = err.as_response_error().error_response();
+    assert_eq!(response.status(), actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+
+    let req = actix_web::test::TestRequest::with_uri("/charges?user_id=user_C3M2XCLwa3LjkkH4V15muQ").to_http_request();
+    let user_id = crate::actix::Query::<UserId>::extract(&req).into_inner().unwrap();
+    assert_eq!(*user_id, "user_C3M2XCLwa3LjkkH4V15muQ");
+    let req = actix_web::test::TestRequest::with_uri("/charges?user=user_C3M2XCLwa3LjkkH4V15muQ").to_http_request();
+    let err = crate::actix::Query::<UserId>::extract(&req).into_inner().unwrap_err();
+    let response = err.as_response_error().error_response();
+    assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[cfg(feature = "ts-rs")]
+#[test]
+fn test_ts_rs() {
+    use ts_rs::{Config, TS};
+
+    let cfg = Config::default();
+    assert_eq!(ChargeId::decl(&cfg), "type ChargeId = `ch_${string}` | `py_${string}`;");
+    assert_eq!(UserOrCharge::decl(&cfg), "type UserOrCharge = ChargeId | UserId;");
+    assert_eq!(Identifier::inline(&cfg), "`${string}_${string}`");
+
+    let exported = UserOrCharge::export_to_string(&cfg).unwrap();
+    assert!(exported.contains("import type { ChargeId } from \"./ChargeId\";"), "{exported}");
+    assert!(exported.contains("export type UserOrCharge = ChargeId | UserId;"), "{exported}");
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn test_python() {
+    use crate::python::PyIdentifier;
+
+    let id = PyIdentifier::generate("ch").unwrap();
+    assert_eq!(id.prefix(), "ch");
+    assert!(PyIdentifier::is_valid(&id.to_string()));
+    assert_eq!(PyIdentifier::parse(&id.to_string()).unwrap(), id);
+
+    assert!(!PyIdentifier::is_valid("ch_"));
+    assert!(PyIdentifier::parse("ch_").is_err());
+    assert!(PyIdentifier::parse("u_a").unwrap() < PyIdentifier::parse("u_b").unwrap());
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi() {
+    use crate::ffi::*;
+    use std::mem::MaybeUninit;
+
+    let mut id = MaybeUninit::<Identifier>::uninit();
+    assert_eq!(unsafe { ei_generate(c"ch".as_ptr(), 2, id.as_mut_ptr()) }, EI_OK);
+    let id = unsafe { id.assume_init() };
+
+    let mut buf = [0; EI_STRING_SIZE];
+    let mut len = 0;
+    assert_eq!(unsafe { ei_to_string(&id, buf.as_mut_ptr(), buf.len(), &mut len) }, EI_OK);
+    assert_eq!(len, id.len());
+    assert_eq!(&buf[..len].iter().map(|&c| c as u8).collect::<Vec<_>>(), id.as_bytes());
+    assert_eq!(buf[len], 0);
+
+    let mut parsed = MaybeUninit::<Identifier>::uninit();
+    assert_eq!(unsafe { ei_parse(buf.as_ptr(), len, parsed.as_mut_ptr()) }, EI_OK);
+    assert_eq!(unsafe { parsed.assume_init() }, id);
+
+    let mut prefix = [0; 3];
+    assert_eq!(unsafe { ei_prefix(&id, prefix.as_mut_ptr(), prefix.len(), std::ptr::null_mut()) }, EI_OK);
+    assert_eq!(prefix, [b'c' as _, b'h' as _, 0]);
+
+    // Too small a buffer is left untouched, but the needed length is still reported
+    let mut small = [1; 2];
+    let mut len = 0;
+    assert_eq!(unsafe { ei_prefix(&id, small.as_mut_ptr(), small.len(), &mut len) }, EI_BUFFER_TOO_SMALL);
+    assert_eq!(small, [1; 2]);
+    assert_eq!(len, 2);
+
+    assert_eq!(unsafe { ei_prefix(std::ptr::null(), buf.as_mut_ptr(), buf.len(), &mut len) }, EI_INVALID);
+    assert_eq!(unsafe { ei_to_string(std::ptr::null(), buf.as_mut_ptr(), buf.len(), &mut len) }, EI_INVALID);
+
+    let mut out = MaybeUninit::<Identifier>::uninit();
+    assert_eq!(unsafe { ei_parse(c"ch_".as_ptr(), 3, out.as_mut_ptr()) }, EI_INVALID);
+    assert_eq!(unsafe { ei_parse(std::ptr::null(), 0, out.as_mut_ptr()) }, EI_INVALID);
+    assert_eq!(unsafe { ei_generate(c"c_h".as_ptr(), 3, out.as_mut_ptr()) }, EI_INVALID);
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn test_any_id() {
+    let any: AnyId = "py_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap();
+    assert_eq!(any.type_name(), "ChargeId");
+    assert_eq!(any.prefix(), "py");
+    assert_eq!(any.to_string(), "py_C3M2XCLwa3LjkkH4V15muQ");
+    assert_eq!(format!("{any:?}"), "ChargeId(py_C3M2XCLwa3LjkkH4V15muQ)");
+    assert!(any.is::<ChargeId>());
+    assert!(!any.is::<UserId>());
+    assert_eq!(any.downcast::<ChargeId>(), Some(ChargeId::from_str("py_C3M2XCLwa3LjkkH4V15muQ").unwrap()));
+    assert_eq!(any.downcast::<UserId>(), None);
+
+    let user_id = UserId::generate();
+    let any = AnyId::from_identifier(*user_id.inner()).unwrap();
+    assert_eq!(any.downcast::<UserId>(), Some(user_id));
+
+    let err = "zz_C3M2XCLwa3LjkkH4V15muQ".parse::<AnyId>().unwrap_err();
+    assert_eq!(err.typename(), "AnyId");
+    assert!("py_".parse::<AnyId>().is_err());
+
+    def_id!(OrderId, "ord", legacy: "order");
+    let legacy: AnyId = "order_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap();
+    assert_eq!(legacy.type_name(), "OrderId");
+    assert!(legacy.is_legacy());
+    assert_eq!(legacy.canonicalize().as_str(), "ord_C3M2XCLwa3LjkkH4V15muQ");
+    assert!(!legacy.canonicalize().is_legacy());
+    assert_eq!(legacy.downcast::<OrderId>().unwrap().canonicalize(), legacy.canonicalize().downcast::<OrderId>().unwrap());
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&any).unwrap();
+        assert_eq!(serde_json::from_str::<AnyId>(&json).unwrap(), any);
+
+        let bytes = postcard::to_allocvec(&any).unwrap();
+        assert_eq!(bytes, postcard::to_allocvec(any.as_bytes()).unwrap());
+        assert_eq!(postcard::from_bytes::<AnyId>(&bytes).unwrap(), any);
+        let bytes = postcard::to_allocvec(b"zz_C3M2XCLwa3LjkkH4V15muQ".as_slice()).unwrap();
+        assert!(postcard::from_bytes::<AnyId>(&bytes).is_err());
+    }
+}
+
+#[cfg(feature = "clap")]
+#[test]
+fn test_clap() {
+    use clap::builder::TypedValueParser;
+    use clap::{value_parser, Arg, Command};
+
+    let cmd = Command::new("admin")
+        .arg(Arg::new("charge").long("charge").value_parser(value_parser!(ChargeId)))
+        .arg(Arg::new("owner").long("owner").value_parser(value_parser!(UserOrAccount)));
+
+    let matches = cmd
+        .clone()
+        .try_get_matches_from(["admin", "--charge", "py_C3M2XCLwa3LjkkH4V15muQ"])
+        .unwrap();
+    assert_eq!(matches.get_one::<ChargeId>("charge").unwrap(), "py_C3M2XCLwa3LjkkH4V15muQ");
+
+    let err = cmd
+        .clone()
+        .try_get_matches_from(["admin", "--charge", "user_C3M2XCLwa3LjkkH4V15muQ"])
+        .unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+    let parse_err = "user_C3M2XCLwa3LjkkH4V15muQ".parse::<ChargeId>().unwrap_err();
+    assert!(err.to_string().contains(&parse_err.to_string()));
+
+    let parser = <UserOrAccount as clap::builder::ValueParserFactory>::value_parser();
+    let hints: Vec<_> = parser.possible_values().unwrap().collect();
+    let names: Vec<_> = hints.iter().map(|hint| hint.get_name()).collect();
+    assert_eq!(names, ["acct_", "user_"]);
+    assert!(hints.iter().all(|hint| !hint.is_hide_set()));
+    assert_eq!(hints[0].get_help().unwrap().to_string(), parser.help());
+    assert_eq!(parser.help(), "A UserOrAccount starting with `acct_` or `user_`");
+    assert_eq!(value_parser!(ChargeId).help(), "A ChargeId starting with `ch_` or `py_`");
+}
+
+// A type whose legacy prefix is one character away from its current one, like the near misses
+// that replace a prefix's last character
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+def_id!(ShipmentId, "shp", legacy: "shx");
+
+#[cfg(any(feature = "arbitrary", feature = "proptest", feature = "quickcheck"))]
+#[test]
+fn test_near_miss_legacy_prefixes() {
+    let valid = ShipmentId::generate();
+    for filler in 0..64 {
+        let near_miss = crate::testing::near_miss(valid.inner(), ShipmentId::PREFIXES, ShipmentId::LEGACY_PREFIXES, 5, filler);
+        assert!(near_miss.parse::<ShipmentId>().is_err(), "{near_miss}");
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    let data: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+    let mut u = Unstructured::new(&data);
+    for _ in 0..32 {
+        let charge_id = ChargeId::arbitrary(&mut u).unwrap();
+        assert!(ChargeId::is_valid_prefix(charge_id.prefix()));
+
+        let id = Identifier::arbitrary(&mut u).unwrap();
+        assert_eq!(Identifier::from_str(id.as_str()).unwrap(), id);
+
+        let near_miss = crate::testing::arbitrary::near_miss(&mut u, ChargeId::prefixes()).unwrap();
+        assert!(near_miss.parse::<ChargeId>().is_err(), "{near_miss}");
+
+        let near_miss = crate::testing::arbitrary::near_miss_for::<ShipmentId>(&mut u).unwrap();
+        assert!(near_miss.parse::<ShipmentId>().is_err(), "{near_miss}");
+    }
+    let _ = UserOrAccount::arbitrary(&mut u).unwrap();
+}
+
+#[cfg(feature = "proptest")]
+proptest::proptest! {
+    #[test]
+    fn test_proptest_ids(charge_id: ChargeId, id: Identifier, either: UserOrAccount) {
+        proptest::prop_assert!(ChargeId::is_valid_prefix(charge_id.prefix()));
+        proptest::prop_assert_eq!(Identifier::from_str(id.as_str()).unwrap(), id);
+        proptest::prop_assert!(matches!(either.inner().prefix(), "acct" | "user"));
+    }
+
+    #[test]
+    fn test_proptest_near_misses(
+        near_miss in crate::testing::proptest::near_misses(ChargeId::prefixes()),
+        any_near_miss in crate::testing::proptest::near_misses(&[]),
+        shipment_near_miss in crate::testing::proptest::near_misses_for::<ShipmentId>(),
+    ) {
+        proptest::prop_assert!(near_miss.parse::<ChargeId>().is_err());
+        proptest::prop_assert!(shipment_near_miss.parse::<ShipmentId>().is_err());
+        proptest::prop_assert!(Identifier::from_str(&any_near_miss).is_err());
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+#[test]
+fn test_quickcheck() {
+    use quickcheck::{quickcheck, Gen};
+
+    fn valid(charge_id: ChargeId, id: Identifier, _either: UserOrAccount) -> bool {
+        ChargeId::is_valid_prefix(charge_id.prefix()) && Identifier::from_str(id.as_str()).unwrap() == id
+    }
+    quickcheck(valid as fn(ChargeId, Identifier, UserOrAccount) -> bool);
+
+    let mut g = Gen::new(32);
+    for _ in 0..256 {
+        let near_miss = crate::testing::quickcheck::near_miss(&mut g, ChargeId::prefixes());
+        assert!(near_miss.parse::<ChargeId>().is_err(), "{near_miss}");
+
+        let near_miss = crate::testing::quickcheck::near_miss_for::<ShipmentId>(&mut g);
+        assert!(near_miss.parse::<ShipmentId>().is_err(), "{near_miss}");
+    }
+}
+
+#[cfg(feature = "wasm")]
+crate::wasm_validator!(ChargeId, "validateChargeId");
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_wasm() {
+    let id = crate::wasm::generate("ch").unwrap();
+    assert!(crate::wasm::validate(&id));
+    assert_eq!(crate::wasm::parse(&id).unwrap(), id);
+    assert_eq!(crate::wasm::prefix(&id).unwrap(), "ch");
+
+    assert!(!crate::wasm::validate("ch_"));
+    assert!(!crate::wasm::validate("chargeid1_C3M2XCLwa3LjkkH4V15muQ"));
+}
+
+/// Hand-picked malformed and boundary inputs for the parsing paths the `fuzz/` targets cover.
+#[test]
+fn test_adversarial_inputs() {
+    let rejected: &[&[u8]] = &[
+        b"",
+        b"_",
+        b"user__C3M2XCLwa3LjkkH4V15muQ",
+        b"user_C3M2X_CLwa3",
+        b"user_C3M2XCLwa3LjkkH4V15muQ\n",
+        b"user_\xff\xfe",
+        "user_C3M2XCLwa3LjkkH4V15mu\u{e9}".as_bytes(),
+        "ch_\u{1F600}".as_bytes(),
+        // 32 bytes, one more than fits in an `Identifier`
+        b"useruser_C3M2XCLwa3LjkkH4V15muQa",
+    ];
+    for input in rejected {
+        assert!(Identifier::from_bytes(input).is_err(), "{input:?}");
+        assert!(ChargeId::from_bytes(input).is_err(), "{input:?}");
+        assert!(UserOrCharge::from_bytes(input).is_err(), "{input:?}");
+        if let Ok(s) = std::str::from_utf8(input) {
+            assert!(s.parse::<ChargeId>().is_err(), "{input:?}");
+            assert!(s.parse::<UserOrCharge>().is_err(), "{input:?}");
+        }
+    }
+
+    // The longest and shortest accepted ids
+    for input in ["useruser_C3M2XCLwa3LjkkH4V15muQ", "u_C"] {
+        let id = Identifier::from_str(input).unwrap();
+        assert_eq!(format!("{}_{}", id.prefix(), id.payload()), input);
+        assert_eq!(unsafe { Identifier::from_bytes_unchecked(input.as_bytes()) }, id);
+    }
+
+    // A typed id and its untyped parse agree on the prefix
+    let charge_id = ChargeId::from_bytes(b"py_0").unwrap();
+    assert_eq!(charge_id, "py_0".parse::<ChargeId>().unwrap());
+    assert_eq!(charge_id.inner().payload_bytes(), Some([0; 16]));
+    assert!(ChargeId::from_bytes(b"pyx_0").is_err());
+}
+
+/// Minimized crashes found by `cargo fuzz run typed`, replayed against the target's checks.
+#[test]
+fn test_fuzz_regressions() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/regressions/typed");
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        let from_bytes = ChargeId::from_bytes(&data);
+        let either = UserOrCharge::from_bytes(&data);
+        if let Ok(s) = std::str::from_utf8(&data) {
+            assert_eq!(s.parse::<ChargeId>().ok(), from_bytes.ok(), "{}", path.display());
+            assert_eq!(s.parse::<UserOrCharge>().ok(), either.ok(), "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn test_entity_id_trait() {
+    use std::collections::BTreeMap;
+
+    /// A minimal generic repository, keyed by any id type.
+    struct Repository<T: EntityId> {
+        rows: BTreeMap<T, &'static str>,
+    }
+
+    impl<T: EntityId> Repository<T> {
+        fn insert(&mut self, value: &'static str) -> T {
+            let id = T::generate();
+            self.rows.insert(id, value);
+            id
+        }
+
+        fn get(&self, id: &str) -> Option<&'static str> {
+            self.rows.get(&T::parse(id).ok()?).copied()
+        }
+    }
+
+    fn describe<T: EntityId>(id: T) -> String {
+        format!("{} {} ({})", T::TYPE_NAME, id.inner(), T::PREFIXES.join("|"))
+    }
+
+    let mut charges = Repository::<ChargeId> { rows: BTreeMap::new() };
+    let charge_id = charges.insert("ten dollars");
+    assert_eq!(charge_id.prefix(), "ch");
+    assert_eq!(charges.get(charge_id.as_str()), Some("ten dollars"));
+    assert_eq!(charges.get("user_C3M2XCLwa3LjkkH4V15muQ"), None);
+    assert_eq!(describe(charge_id), format!("ChargeId {charge_id} (ch|py)"));
+
+    assert_eq!(<ChargeId as EntityId>::DEFAULT_PREFIX, "ch");
+    assert_eq!(<UserOrCharge as EntityId>::DEFAULT_PREFIX, "ch");
+    assert_eq!(<Reference as EntityId>::DEFAULT_PREFIX, "card");
+    assert_eq!(<Reference as EntityId>::PREFIXES, Reference::PREFIXES);
+    assert_eq!(<Reference as EntityId>::TYPE_NAME, "Reference");
+
+    let mut references = Repository::<Reference> { rows: BTreeMap::new() };
+    let reference = references.insert("a card");
+    assert!(matches!(reference, Reference::Source(PaymentSource::Card(_))));
+    assert_eq!(describe(reference), format!("Reference {reference} (card|ba|wal|ch|py)"));
+
+    let id = Identifier::from_str("py_C3M2XCLwa3LjkkH4V15muQ").unwrap();
+    assert_eq!(ChargeId::from_identifier(id).unwrap().as_str(), id.as_str());
+    assert!(matches!(UserOrCharge::from_identifier(id).unwrap(), UserOrCharge::Charge(_)));
+    assert!(matches!(Reference::from_identifier(id).unwrap(), Reference::Charge(_)));
+    let err = UserId::from_identifier(id).unwrap_err();
+    assert_eq!(err.to_string(), "invalid `UserId`, expected an id starting with `user_`");
+    assert!(UserOrAccount::from_identifier(id).is_err());
+}
+
+#[test]
+fn test_identifier_conversions() {
+    let id = Identifier::from_str("py_C3M2XCLwa3LjkkH4V15muQ").unwrap();
+
+    let charge_id = ChargeId::try_from(id).unwrap();
+    assert_eq!(Identifier::from(charge_id), id);
+    assert_eq!(id.try_into_typed::<ChargeId>().unwrap(), charge_id);
+
+    let err = UserId::try_from(id).unwrap_err();
+    assert_eq!(err.to_string(), "invalid `UserId`, expected an id starting with `user_`");
+    assert!(id.try_into_typed::<UserOrAccount>().is_err());
+
+    let reference: Reference = id.try_into().unwrap();
+    assert_eq!(reference, Reference::Charge(charge_id));
+    assert_eq!(Identifier::from(reference), id);
+
+    let card = Identifier::from_str("card_0").unwrap().try_into_typed::<PaymentSource>().unwrap();
+    assert!(matches!(card, PaymentSource::Card(_)));
+    assert_eq!(Identifier::from(card).as_str(), "card_0");
+
+    assert!(ChargeId::from_bytes(b"ch_").is_err());
+    assert!(UserOrCharge::from_bytes(b"py_0").is_ok());
+    assert!(UserOrCharge::from_bytes(b"acct_0").is_err());
+    assert!(UserOrCharge::from_bytes(b"user").is_err());
+}
+
+#[test]
+fn test_legacy_prefixes() {
+    use std::sync::Mutex;
+
+    static SEEN: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    // Other tests parse legacy ids of their own types concurrently, so only record `CustomerId`s
+    legacy::set_hook(|type_name, id| {
+        if type_name == "CustomerId" {
+            SEEN.lock().unwrap().push(format!("{type_name} {id}"));
+        }
+    });
+
+    assert_eq!(CustomerId::PREFIXES, ["cus"]);
+    assert_eq!(<CustomerId as EntityId>::PREFIXES, ["cus"]);
+    assert_eq!(CustomerId::LEGACY_PREFIXES, ["cust", "cu"]);
+    assert!(CustomerId::is_valid_prefix("cust"));
+    assert!(CustomerId::is_legacy_prefix("cust"));
+    assert!(!CustomerId::is_legacy_prefix("cus"));
+
+    let legacy_id: CustomerId = "cust_C3M2XCLwa3LjkkH4V15muQ".parse().unwrap();
+    assert!(legacy_id.is_legacy());
+    let canonical = legacy_id.canonicalize();
+    assert!(!canonical.is_legacy());
+    assert_eq!(canonical.as_str(), "cus_C3M2XCLwa3LjkkH4V15muQ");
+    assert_eq!(canonical.canonicalize(), canonical);
+    assert!(CustomerId::from_bytes(b"cu_0").unwrap().is_legacy());
+
+    for _ in 0..100 {
+        assert_eq!(CustomerId::generate().prefix(), "cus");
+    }
+    assert!(CustomerId::generate_with_prefix("cust").is_none());
+    assert_eq!(CustomerId::generate_with_prefix("cus").unwrap().prefix(), "cus");
+
+    let generic: CustomerId = EntityId::parse("cu_C3M2XCLwa3LjkkH4V15muQ").unwrap();
+    assert!(EntityId::is_legacy(&generic));
+    assert_eq!(<CustomerId as EntityId>::LEGACY_PREFIXES, ["cust", "cu"]);
+
+    def_id!(
+        enum Party {
+            User(UserId),
+            Customer(CustomerId),
+        }
+    );
+    assert_eq!(Party::PREFIXES, ["user", "cus"]);
+    assert_eq!(Party::LEGACY_PREFIXES, ["cust", "cu"]);
+    let party: Party = "cu_0".parse().unwrap();
+    assert!(party.is_legacy());
+    assert_eq!(party.canonicalize().as_str(), "cus_0");
+    assert!(Party::generate_with_prefix("cu").is_none());
+    assert!(!Party::is_legacy_prefix("user"));
+
+    let err = "cx_C3M2XCLwa3LjkkH4V15muQ".parse::<CustomerId>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid `CustomerId`, expected an id starting with `cus_`");
+    let err = "cx_0".parse::<Party>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid `Party`, expected an id starting with `user_` or `cus_`");
+
+    let id = Identifier::from_str("cust_0").unwrap();
+    assert_eq!(id.with_prefix("cus").unwrap().as_str(), "cus_0");
+    assert!(id.with_prefix("c_s").is_err());
+    assert_eq!(id.with_prefix("abcdefgh").unwrap().as_str(), "abcdefgh_0");
+
+    assert!(legacy::take_hook().is_some());
+    assert!(legacy::take_hook().is_none());
+    "cust_C3M2XCLwa3LjkkH4V15muQ".parse::<CustomerId>().unwrap();
+
+    let seen = SEEN.lock().unwrap();
+    assert_eq!(
+        *seen,
+        [
+            "CustomerId cust_C3M2XCLwa3LjkkH4V15muQ",
+            "CustomerId cu_0",
+            "CustomerId cu_C3M2XCLwa3LjkkH4V15muQ",
+            "CustomerId cu_0",
+        ],
+    );
+}
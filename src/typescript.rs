@@ -0,0 +1,113 @@
+//! TypeScript definitions via `ts-rs`.
+//!
+//! `def_id!` types are exported as template literal types, so the frontend cannot mix them up:
+//! ``type ChargeId = `ch_${string}` | `py_${string}`;``. `def_id!(enum ...)` unions are exported
+//! as unions of their variants, e.g. `type UserOrCharge = ChargeId | UserId;`.
+
+use ts_rs::{Config, TS};
+
+use crate::Identifier;
+
+impl TS for Identifier {
+    type WithoutGenerics = Self;
+    type OptionInnerType = Self;
+
+    fn name(_cfg: &Config) -> String {
+        "Identifier".into()
+    }
+
+    fn inline(_cfg: &Config) -> String {
+        "`${string}_${string}`".into()
+    }
+
+    fn decl(cfg: &Config) -> String {
+        decl("Identifier", &Self::inline(cfg))
+    }
+
+    fn decl_concrete(cfg: &Config) -> String {
+        Self::decl(cfg)
+    }
+
+    fn output_path() -> Option<std::path::PathBuf> {
+        Some("Identifier.ts".into())
+    }
+}
+
+/// The template literal type of ids starting with one of `prefixes`.
+#[doc(hidden)]
+pub fn template_union(prefixes: &[&str]) -> String {
+    prefixes
+        .iter()
+        .map(|prefix| format!("`{prefix}_${{string}}`"))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[doc(hidden)]
+pub fn decl(name: &str, inline: &str) -> String {
+    format!("type {name} = {inline};")
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_id_ts_rs_impls {
+    ($struct_name:ident, $prefix:literal $(| $alt_prefix:literal)*) => {
+        impl $crate::__private::ts_rs::TS for $struct_name {
+            type WithoutGenerics = Self;
+            type OptionInnerType = Self;
+
+            fn name(_cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                stringify!($struct_name).into()
+            }
+
+            fn inline(_cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                $crate::typescript::template_union(&[$prefix $(, $alt_prefix)*])
+            }
+
+            fn decl(cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                $crate::typescript::decl(stringify!($struct_name), &Self::inline(cfg))
+            }
+
+            fn decl_concrete(cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                Self::decl(cfg)
+            }
+
+            fn output_path() -> Option<$crate::__private::PathBuf> {
+                Some(concat!(stringify!($struct_name), ".ts").into())
+            }
+        }
+    };
+    (enum $enum_name:ident { $( $variant_name:ident($($variant_type:tt)*) ),+ }) => {
+        impl $crate::__private::ts_rs::TS for $enum_name {
+            type WithoutGenerics = Self;
+            type OptionInnerType = Self;
+
+            fn name(_cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                stringify!($enum_name).into()
+            }
+
+            fn inline(cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                [$( <$($variant_type)* as $crate::__private::ts_rs::TS>::name(cfg), )*].join(" | ")
+            }
+
+            fn decl(cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                $crate::typescript::decl(stringify!($enum_name), &Self::inline(cfg))
+            }
+
+            fn decl_concrete(cfg: &$crate::__private::ts_rs::Config) -> $crate::__private::String {
+                Self::decl(cfg)
+            }
+
+            fn visit_dependencies(v: &mut impl $crate::__private::ts_rs::TypeVisitor)
+            where
+                Self: 'static,
+            {
+                $( v.visit::<$($variant_type)*>(); )*
+            }
+
+            fn output_path() -> Option<$crate::__private::PathBuf> {
+                Some(concat!(stringify!($enum_name), ".ts").into())
+            }
+        }
+    };
+}
@@ -0,0 +1,62 @@
+//! WebAssembly bindings through `wasm-bindgen`.
+//!
+//! The exported `parse`, `validate`, `prefix` and `generate` functions work on any id. Validators
+//! for specific `def_id!` types are exported with [`wasm_validator!`](crate::wasm_validator).
+//! `generate` draws from the browser's `crypto.getRandomValues`.
+
+use alloc::string::{String, ToString};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Identifier, InvalidIdentifierError};
+
+fn js_error(err: InvalidIdentifierError) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+/// Parses an id, returning it unchanged or throwing if it is invalid.
+#[wasm_bindgen]
+pub fn parse(id: &str) -> Result<String, JsError> {
+    Identifier::from_str(id).map(String::from).map_err(js_error)
+}
+
+/// Whether `id` is a valid id of any type.
+#[wasm_bindgen]
+pub fn validate(id: &str) -> bool {
+    Identifier::from_str(id).is_ok()
+}
+
+/// The prefix of an id, throwing if it is invalid.
+#[wasm_bindgen]
+pub fn prefix(id: &str) -> Result<String, JsError> {
+    Identifier::from_str(id).map(|id| id.prefix().to_string()).map_err(js_error)
+}
+
+/// Generates a new id with the given prefix.
+#[wasm_bindgen]
+pub fn generate(prefix: &str) -> Result<String, JsError> {
+    Identifier::generate(prefix).map(String::from).map_err(js_error)
+}
+
+/// Exports a validator for a `def_id!` type under the given JavaScript name.
+///
+/// ```ignore
+/// def_id!(UserId, "user", {"user_C3M2XCLwa3LjkkH4V15muQ"});
+/// entity_ident::wasm_validator!(UserId, "validateUserId");
+/// ```
+///
+/// `validateUserId("user_...")` is then `true`, and `false` for ids of other types.
+#[macro_export]
+macro_rules! wasm_validator {
+    ($type_name:ty, $js_name:literal) => {
+        const _: () = {
+            #[$crate::__private::wasm_bindgen::prelude::wasm_bindgen(
+                js_name = $js_name,
+                wasm_bindgen = $crate::__private::wasm_bindgen
+            )]
+            pub fn validate(id: &str) -> bool {
+                <$type_name as ::core::str::FromStr>::from_str(id).is_ok()
+            }
+        };
+    };
+}
@@ -0,0 +1,7 @@
+use entity_ident::def_id;
+
+def_id!(LongId, "this_is_too_long");
+def_id!(EmptyId, "");
+def_id!(ChargeId, "ch" | "p-y");
+
+fn main() {}
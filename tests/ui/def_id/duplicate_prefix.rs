@@ -0,0 +1,6 @@
+use entity_ident::def_id;
+
+def_id!(ChargeId, "ch" | "py" | "ch");
+def_id!(CustomerId, "cus", legacy: "cust" | "cus");
+
+fn main() {}
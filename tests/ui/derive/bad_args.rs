@@ -0,0 +1,15 @@
+use entity_ident::{EntityId, Identifier};
+
+#[derive(EntityId)]
+#[entity_id(prefix = "user", visibility = "pub")]
+struct UserId(Identifier);
+
+#[derive(EntityId)]
+#[entity_id(prefix = "acct", vis = "public")]
+struct AccountId(Identifier);
+
+#[derive(EntityId)]
+#[entity_id(prefix = "org", alt = [1])]
+struct OrgId(Identifier);
+
+fn main() {}
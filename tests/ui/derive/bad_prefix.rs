@@ -0,0 +1,15 @@
+use entity_ident::{EntityId, Identifier};
+
+#[derive(EntityId)]
+#[entity_id(prefix = "ch_")]
+struct ChargeId(Identifier);
+
+#[derive(EntityId)]
+#[entity_id(prefix = "customers")]
+struct CustomerId(Identifier);
+
+#[derive(EntityId)]
+#[entity_id(prefix = "ch", alt = ["py", "ch"])]
+struct PaymentId(Identifier);
+
+fn main() {}
@@ -0,0 +1,16 @@
+use entity_ident::{EntityId, Identifier};
+
+#[derive(EntityId)]
+#[entity_id(prefix = "user")]
+struct UserId {
+    id: Identifier,
+}
+
+#[derive(EntityId)]
+#[entity_id(prefix = "acct")]
+struct AccountId(String);
+
+#[derive(EntityId)]
+struct OrgId(Identifier);
+
+fn main() {}